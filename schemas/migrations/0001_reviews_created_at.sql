@@ -0,0 +1,10 @@
+-- Needed for review sorting and cursor pagination
+ALTER TABLE reviews ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ;
+
+-- Existing reviews keep their original time (the first entry of epoch)
+UPDATE reviews SET created_at = COALESCE(to_timestamp(epoch[1]), NOW()) WHERE created_at IS NULL;
+
+ALTER TABLE reviews ALTER COLUMN created_at SET DEFAULT NOW();
+ALTER TABLE reviews ALTER COLUMN created_at SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS reviews_target_created_at_idx ON reviews (target_id, target_type, created_at DESC, id DESC) WHERE parent_id IS NULL;
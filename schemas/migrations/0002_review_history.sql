@@ -0,0 +1,9 @@
+-- Prior revisions of a review, one row is added every time a review is edited
+CREATE TABLE IF NOT EXISTS review_history (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    review_id UUID NOT NULL REFERENCES reviews (id) ON DELETE CASCADE ON UPDATE CASCADE,
+    star_rating NUMERIC(4, 2) NOT NULL,
+    review_text TEXT NOT NULL,
+    edited_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS review_history_review_id_idx ON review_history (review_id, edited_at DESC);
@@ -0,0 +1,35 @@
+-- Typo tolerance below uses trigram similarity
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Weighted full text search. Owners are matched separately and only weighted at query time
+-- as they live in other tables
+-- Weights: A = name, B = short description, C = long description, D = owner
+ALTER TABLE bots ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', COALESCE(username_cached, '')), 'A') ||
+    setweight(to_tsvector('simple', COALESCE(description, '')), 'B') ||
+    setweight(to_tsvector('simple', COALESCE(long_description, '')), 'C')
+) STORED;
+
+ALTER TABLE servers ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', COALESCE(name_cached, '')), 'A') ||
+    setweight(to_tsvector('simple', COALESCE(description, '')), 'B') ||
+    setweight(to_tsvector('simple', COALESCE(long_description, '')), 'C')
+) STORED;
+
+ALTER TABLE bot_packs ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', COALESCE(name, '')), 'A') ||
+    setweight(to_tsvector('simple', COALESCE(description, '')), 'B')
+) STORED;
+
+CREATE INDEX IF NOT EXISTS bots_search_vector_idx ON bots USING GIN (search_vector);
+CREATE INDEX IF NOT EXISTS servers_search_vector_idx ON servers USING GIN (search_vector);
+CREATE INDEX IF NOT EXISTS bot_packs_search_vector_idx ON bot_packs USING GIN (search_vector);
+
+-- Typo tolerance
+CREATE INDEX IF NOT EXISTS bots_username_cached_trgm_idx ON bots USING GIN (username_cached gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS servers_name_cached_trgm_idx ON servers USING GIN (name_cached gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS users_username_trgm_idx ON users USING GIN (username gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS bot_packs_name_trgm_idx ON bot_packs USING GIN (name gin_trgm_ops);
+
+-- Owner matching
+CREATE INDEX IF NOT EXISTS users_username_search_idx ON users USING GIN (to_tsvector('simple', COALESCE(username, '')));
@@ -0,0 +1,8 @@
+-- Precomputed similar bots, refreshed periodically
+CREATE TABLE IF NOT EXISTS bot_similar (
+    bot_id BIGINT NOT NULL REFERENCES bots (bot_id) ON DELETE CASCADE ON UPDATE CASCADE,
+    similar_bot_id BIGINT NOT NULL REFERENCES bots (bot_id) ON DELETE CASCADE ON UPDATE CASCADE,
+    score DOUBLE PRECISION NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (bot_id, similar_bot_id)
+);
@@ -0,0 +1,2 @@
+-- Per-user NSFW preference used by the index
+ALTER TABLE users ADD COLUMN IF NOT EXISTS show_nsfw BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,34 @@
+-- Pack bots are stored in their own table so they can be ordered and have notes
+CREATE TABLE IF NOT EXISTS bot_pack_bots (
+    pack_id UUID NOT NULL REFERENCES bot_packs (id) ON DELETE CASCADE,
+    bot_id BIGINT NOT NULL REFERENCES bots (bot_id) ON DELETE CASCADE ON UPDATE CASCADE,
+    position INTEGER NOT NULL,
+    note TEXT,
+    PRIMARY KEY (pack_id, bot_id)
+);
+
+INSERT INTO bot_pack_bots (pack_id, bot_id, position)
+SELECT bot_packs.id, pack_bots.bot_id, MIN(pack_bots.position)::integer - 1
+FROM bot_packs
+CROSS JOIN unnest(bot_packs.bots) WITH ORDINALITY AS pack_bots(bot_id, position)
+INNER JOIN bots ON bots.bot_id = pack_bots.bot_id
+GROUP BY bot_packs.id, pack_bots.bot_id
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE bot_packs DROP COLUMN IF EXISTS bots;
+
+-- 0 = Public, 1 = Unlisted
+ALTER TABLE bot_packs ADD COLUMN IF NOT EXISTS visibility INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS bot_pack_editors (
+    pack_id UUID NOT NULL REFERENCES bot_packs (id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL,
+    PRIMARY KEY (pack_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS bot_pack_followers (
+    pack_id UUID NOT NULL REFERENCES bot_packs (id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (pack_id, user_id)
+);
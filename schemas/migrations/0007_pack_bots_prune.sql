@@ -0,0 +1,2 @@
+-- Deleted bots are kept in packs until the prune task removes them (and notifies the owner)
+ALTER TABLE bot_pack_bots DROP CONSTRAINT IF EXISTS bot_pack_bots_bot_id_fkey;
@@ -0,0 +1,15 @@
+-- Command args are now objects with a name, type, whether they are required and choices
+ALTER TABLE bot_commands ADD COLUMN IF NOT EXISTS args_structured JSONB NOT NULL DEFAULT '[]';
+
+UPDATE bot_commands SET args_structured = COALESCE((
+    SELECT jsonb_agg(jsonb_build_object(
+        'name', trim(BOTH '<>[] ' FROM arg),
+        'arg_type', 0,
+        'required', trim(arg) LIKE '<%',
+        'choices', '[]'::jsonb
+    ) ORDER BY position)
+    FROM unnest(bot_commands.args) WITH ORDINALITY AS cmd_args(arg, position)
+), '[]'::jsonb);
+
+ALTER TABLE bot_commands DROP COLUMN args;
+ALTER TABLE bot_commands RENAME COLUMN args_structured TO args;
@@ -0,0 +1,12 @@
+-- Bot events can be scheduled and expire
+ALTER TABLE bot_events ADD COLUMN IF NOT EXISTS starts_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE bot_events ADD COLUMN IF NOT EXISTS ends_at TIMESTAMPTZ;
+
+-- Whether the start and end of an event were sent over the websocket gateway
+ALTER TABLE bot_events ADD COLUMN IF NOT EXISTS start_announced BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE bot_events ADD COLUMN IF NOT EXISTS end_announced BOOLEAN NOT NULL DEFAULT false;
+
+-- Existing events started when they were created and should not be announced again
+UPDATE bot_events SET starts_at = ts, start_announced = true, end_announced = true;
+
+CREATE INDEX IF NOT EXISTS bot_events_bot_id_idx ON bot_events (bot_id);
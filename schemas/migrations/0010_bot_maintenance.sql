@@ -0,0 +1,2 @@
+-- Maintenance events can pause invites while they are active
+ALTER TABLE bot_events ADD COLUMN IF NOT EXISTS pause_invites BOOLEAN NOT NULL DEFAULT false;
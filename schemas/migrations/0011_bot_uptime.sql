@@ -0,0 +1,23 @@
+-- Results of every uptime check, checks older than 31 days are pruned daily
+CREATE TABLE IF NOT EXISTS bot_uptime_checks (
+    bot_id BIGINT NOT NULL REFERENCES bots (bot_id) ON DELETE CASCADE ON UPDATE CASCADE,
+    online BOOLEAN NOT NULL,
+    -- Checks during maintenance are kept but not counted against the bot
+    in_maintenance BOOLEAN NOT NULL DEFAULT false,
+    checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS bot_uptime_checks_bot_id_idx ON bot_uptime_checks (bot_id, checked_at);
+CREATE INDEX IF NOT EXISTS bot_uptime_checks_checked_at_idx ON bot_uptime_checks (checked_at);
+
+-- A bot is in an incident from its first failed check until its next successful one
+CREATE TABLE IF NOT EXISTS bot_uptime_incidents (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    bot_id BIGINT NOT NULL REFERENCES bots (bot_id) ON DELETE CASCADE ON UPDATE CASCADE,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ended_at TIMESTAMPTZ,
+    owners_notified BOOLEAN NOT NULL DEFAULT false
+);
+
+CREATE INDEX IF NOT EXISTS bot_uptime_incidents_bot_id_idx ON bot_uptime_incidents (bot_id, started_at);
+CREATE UNIQUE INDEX IF NOT EXISTS bot_uptime_incidents_open_idx ON bot_uptime_incidents (bot_id) WHERE ended_at IS NULL;
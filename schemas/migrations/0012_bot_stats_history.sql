@@ -0,0 +1,14 @@
+-- Every stats post is kept for a year so growth can be charted
+CREATE TABLE IF NOT EXISTS bot_stats_history (
+    bot_id BIGINT NOT NULL REFERENCES bots (bot_id) ON DELETE CASCADE ON UPDATE CASCADE,
+    guild_count BIGINT NOT NULL,
+    shard_count BIGINT,
+    user_count BIGINT,
+    -- Guild count of each shard, the index is the shard id
+    shard_guilds BIGINT[],
+    -- Set when the post looked suspicious but was not rejected
+    anomaly TEXT,
+    posted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS bot_stats_history_bot_id_idx ON bot_stats_history (bot_id, posted_at);
@@ -0,0 +1,4 @@
+-- Stats posted while application info was unavailable are verified later
+ALTER TABLE bot_stats_history ADD COLUMN IF NOT EXISTS pending_verification BOOLEAN NOT NULL DEFAULT false;
+
+CREATE INDEX IF NOT EXISTS bot_stats_history_pending_idx ON bot_stats_history (bot_id) WHERE pending_verification;
@@ -31,7 +31,9 @@ async fn appeal_bot(
     let rl = data.database.get_ratelimit(models::Ratelimit::Appeal, user_id).await;
 
     if rl.is_some() && rl.unwrap() > 0 {
-        return HttpResponse::BadRequest().json(models::APIResponse::rl(rl.unwrap()));
+        return HttpResponse::build(http::StatusCode::TOO_MANY_REQUESTS)
+            .insert_header(("Retry-After", rl.unwrap().to_string()))
+            .json(models::APIResponse::rl(rl.unwrap()));
     }
 
     let bot = data.database.get_bot(bot_id).await;
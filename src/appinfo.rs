@@ -0,0 +1,231 @@
+/// Application info (bot id, whether the bot is public and its approximate guild count) of bots
+///
+/// Application info comes from an ``AppInfoProvider``. To add a new provider, implement
+/// ``AppInfoProvider`` and add it to ``provider``.
+///
+/// Only ``Japi`` gives approximate guild counts. With the ``Discord`` and ``Offline`` providers
+/// the guild count sanity check and the anomaly checks against the approximate count in
+/// ``post_stats`` are skipped entirely, only sudden jumps are still detected
+use crate::models;
+use actix_web::web;
+use futures::future::BoxFuture;
+use log::{debug, error, warn};
+use moka::future::Cache;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub enum AppInfoError {
+    /// The application does not exist or has no bot
+    NotFound,
+    /// The provider could not be reached, the application may still exist
+    Unavailable(String),
+}
+
+pub trait AppInfoProvider: Send + Sync {
+    fn fetch(&self, client_id: i64) -> BoxFuture<'_, Result<models::ApplicationInfo, AppInfoError>>;
+}
+
+/// japi.rest
+pub struct Japi {
+    pub key: String,
+    pub client: reqwest::Client,
+}
+
+impl AppInfoProvider for Japi {
+    fn fetch(&self, client_id: i64) -> BoxFuture<'_, Result<models::ApplicationInfo, AppInfoError>> {
+        Box::pin(async move {
+            let resp = self.client
+                .get(format!("https://japi.rest/discord/v1/application/{}", client_id))
+                .timeout(Duration::from_secs(10))
+                .header("Authorization", &self.key)
+                .send()
+                .await
+                .map_err(|err| AppInfoError::Unavailable(err.to_string()))?;
+
+            // Anything but a 404 (such as a bad key or being rate limited) says nothing about
+            // whether the application exists
+            let status = resp.status();
+            if status == reqwest::StatusCode::NOT_FOUND {
+                return Err(AppInfoError::NotFound);
+            } else if !status.is_success() {
+                return Err(AppInfoError::Unavailable(format!("japi.rest returned {}", status)));
+            }
+
+            let app: models::JAPIApplication = resp
+                .json()
+                .await
+                .map_err(|err| AppInfoError::Unavailable(err.to_string()))?;
+
+            Ok(models::ApplicationInfo {
+                bot_id: app.data.bot.id,
+                bot_public: app.data.application.bot_public,
+                approximate_guild_count: Some(app.data.bot.approximate_guild_count),
+            })
+        })
+    }
+}
+
+/// The Discord API. Whether the bot is public comes from the (unauthenticated) rpc endpoint
+/// of the application and the bot itself is fetched through the main bot. Discord does not
+/// give out approximate guild counts of other applications
+pub struct DiscordApi {
+    pub http: Arc<serenity::http::Http>,
+    pub client: reqwest::Client,
+}
+
+#[derive(serde::Deserialize)]
+struct DiscordRpcApplication {
+    bot_public: bool,
+}
+
+impl AppInfoProvider for DiscordApi {
+    fn fetch(&self, client_id: i64) -> BoxFuture<'_, Result<models::ApplicationInfo, AppInfoError>> {
+        Box::pin(async move {
+            let resp = self.client
+                .get(format!("https://discord.com/api/v10/applications/{}/rpc", client_id))
+                .timeout(Duration::from_secs(10))
+                .send()
+                .await
+                .map_err(|err| AppInfoError::Unavailable(err.to_string()))?;
+
+            let status = resp.status();
+            if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::BAD_REQUEST {
+                return Err(AppInfoError::NotFound);
+            } else if !status.is_success() {
+                return Err(AppInfoError::Unavailable(format!("Discord returned {}", status)));
+            }
+
+            let app: DiscordRpcApplication = resp
+                .json()
+                .await
+                .map_err(|err| AppInfoError::Unavailable(err.to_string()))?;
+
+            // The bot of an application has the same id as the application
+            let user = self.http
+                .get_user(client_id as u64)
+                .await
+                .map_err(|err| AppInfoError::Unavailable(err.to_string()))?;
+
+            if !user.bot {
+                return Err(AppInfoError::NotFound);
+            }
+
+            Ok(models::ApplicationInfo {
+                bot_id: user.id.0.to_string(),
+                bot_public: app.bot_public,
+                approximate_guild_count: None,
+            })
+        })
+    }
+}
+
+/// Makes no requests and trusts every client id, used for testing without network access
+pub struct Offline;
+
+impl AppInfoProvider for Offline {
+    fn fetch(&self, client_id: i64) -> BoxFuture<'_, Result<models::ApplicationInfo, AppInfoError>> {
+        Box::pin(async move {
+            Ok(models::ApplicationInfo {
+                bot_id: client_id.to_string(),
+                bot_public: true,
+                approximate_guild_count: None,
+            })
+        })
+    }
+}
+
+pub fn provider(
+    config: &models::AppConfig,
+    discord_http: Arc<serenity::http::Http>,
+    client: reqwest::Client,
+) -> Box<dyn AppInfoProvider> {
+    match config.app_info.provider {
+        models::AppInfoProviderType::Japi => Box::new(Japi {
+            key: config.secrets.japi_key.clone(),
+            client,
+        }),
+        models::AppInfoProviderType::Discord => Box::new(DiscordApi {
+            http: discord_http,
+            client,
+        }),
+        models::AppInfoProviderType::Offline => Box::new(Offline),
+    }
+}
+
+/// Application info provider with a per client id cache in front of it
+pub struct AppInfo {
+    provider: Box<dyn AppInfoProvider>,
+    cache: Cache<i64, (models::ApplicationInfo, Instant)>,
+    fresh_for: Duration,
+}
+
+impl AppInfo {
+    pub fn new(config: &models::AppConfig, provider: Box<dyn AppInfoProvider>) -> Self {
+        AppInfo {
+            provider,
+            cache: Cache::builder()
+                .time_to_live(Duration::from_secs(config.app_info.keep_hours * 60 * 60))
+                .max_capacity(50000)
+                .build(),
+            fresh_for: Duration::from_secs(config.app_info.fresh_minutes * 60),
+        }
+    }
+
+    /// Gets the application info of a client id. If the provider is unavailable, older
+    /// cached info is used instead
+    pub async fn get(&self, client_id: i64) -> Result<models::ApplicationInfo, AppInfoError> {
+        let cached = self.cache.get(&client_id);
+
+        if let Some((ref info, fetched_at)) = cached {
+            if fetched_at.elapsed() < self.fresh_for {
+                return Ok(info.clone());
+            }
+        }
+
+        match self.provider.fetch(client_id).await {
+            Ok(info) => {
+                self.cache.insert(client_id, (info.clone(), Instant::now())).await;
+                Ok(info)
+            },
+            Err(AppInfoError::NotFound) => {
+                self.cache.invalidate(&client_id).await;
+                Err(AppInfoError::NotFound)
+            },
+            Err(AppInfoError::Unavailable(err)) => match cached {
+                Some((info, _)) => {
+                    warn!("Using cached application info for {}: {}", client_id, err);
+                    Ok(info)
+                },
+                None => Err(AppInfoError::Unavailable(err)),
+            },
+        }
+    }
+}
+
+/// Verifies stats that were accepted while application info was unavailable
+pub async fn verify_pending_stats(state: web::Data<models::AppState>) {
+    let pending = state.database.get_pending_stats_bots().await;
+
+    if pending.is_empty() {
+        return;
+    }
+
+    debug!("Verifying stats of {} bots", pending.len());
+
+    for (bot_id, client_id) in pending {
+        let approx_count = match state.app_info.get(client_id).await {
+            Ok(info) => info.approximate_guild_count,
+            Err(AppInfoError::NotFound) => {
+                warn!("Application {} of bot {} not found, stats left unverified", client_id, bot_id);
+                None
+            },
+            // Still down, try again next time
+            Err(AppInfoError::Unavailable(_)) => return,
+        };
+
+        if let Err(err) = state.database.verify_pending_stats(bot_id, approx_count).await {
+            error!("Error verifying stats of {}: {}", bot_id, err);
+        }
+    }
+}
@@ -1,10 +1,11 @@
 /// Handles bot actions (view, add, edit, delete, transfer)
 
+use crate::appinfo;
 use crate::models;
 use crate::converters;
 use actix_web::http::header::HeaderValue;
-use actix_web::{get, delete, patch, post, web, http, web::Json, HttpRequest, HttpResponse};
-use log::{error, debug};
+use actix_web::{get, delete, patch, post, web, http, HttpRequest, HttpResponse};
+use log::{error, debug, warn};
 use serenity::model::prelude::*;
 use std::time::Duration;
 use std::collections::HashMap;
@@ -74,7 +75,6 @@ async fn check_bot(
             } 
             return Err(models::CheckBotError::AlreadyExists);
         }
-        // TODO JAPI checks
 
         let mut id = bot_id;
 
@@ -85,32 +85,18 @@ async fn check_bot(
                 .map_err(|_| models::CheckBotError::BotNotFound)?;
         }
 
-        let resp = data
-            .requests
-            .get(format!(
-                "https://japi.rest/discord/v1/application/{bot_id}",
-                bot_id = id
-            ))
-            .timeout(Duration::from_secs(10))
-            .header("Authorization", data.config.secrets.japi_key.clone())
-            .send()
-            .await
-            .map_err(models::CheckBotError::JAPIError)?;
-        let status = resp.status();
-        if !status.is_success() {
-            return Err(models::CheckBotError::ClientIDNeeded);
-        }
-        let resp_json: models::JAPIApplication = resp
-            .json()
-            .await
-            .map_err(models::CheckBotError::JAPIDeserError)?;
-        if resp_json.data.bot.id != bot_id.to_string() && bot_id.to_string() != bot.client_id {
+        let app = match data.app_info.get(id).await {
+            Ok(app) => app,
+            Err(appinfo::AppInfoError::NotFound) => return Err(models::CheckBotError::ClientIDNeeded),
+            Err(appinfo::AppInfoError::Unavailable(err)) => return Err(models::CheckBotError::AppInfoUnavailable(err)),
+        };
+        if app.bot_id != bot_id.to_string() && bot_id.to_string() != bot.client_id {
             return Err(models::CheckBotError::InvalidClientID);
         }
-        if !resp_json.data.application.bot_public {
+        if !app.bot_public {
             return Err(models::CheckBotError::PrivateBot);
         }
-        bot.guild_count = resp_json.data.bot.approximate_guild_count;
+        bot.guild_count = app.approximate_guild_count.unwrap_or(0);
     } else {
         if let Some(ref bot_res) = bot_dat {
             if !bot_res.client_id.is_empty() && bot_res.client_id != bot.client_id {
@@ -1074,8 +1060,27 @@ async fn post_stats(
             return HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::GenericError::APIBan("StatsLocked".to_string())));
         }
 
-        let resp = data.database.post_stats(bot_id, bot.client_id.parse().unwrap_or(0), stats.into_inner(), &data.config.secrets.japi_key).await;
+        // If application info is unavailable, the stats are accepted and verified later
+        let app = match data.app_info.get(bot.client_id.parse().unwrap_or(0)).await {
+            Ok(app) => Some(app),
+            Err(appinfo::AppInfoError::NotFound) => {
+                return HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::StatsError::ClientIDNeeded));
+            },
+            Err(appinfo::AppInfoError::Unavailable(err)) => {
+                warn!("Deferring stats verification of {}: {}", bot_id, err);
+                None
+            },
+        };
+
+        let pending = app.is_none();
+
+        let resp = data.database.post_stats(bot_id, stats.into_inner(), app).await;
         match resp {
+            Ok(()) if pending => HttpResponse::build(http::StatusCode::ACCEPTED).json(models::APIResponse {
+                done: true,
+                reason: Some("Stats will be verified once application info is available".to_string()),
+                context: None,
+            }),
             Ok(()) => HttpResponse::build(http::StatusCode::OK).json(models::APIResponse::ok()),
             Err(err) => {
                 HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&err))
@@ -1093,6 +1098,11 @@ async fn get_bot(req: HttpRequest, id: web::Path<models::FetchBotPath>) -> HttpR
 
     let id = id.into_inner();
 
+    // Maintenance events may pause invites
+    if req.headers().contains_key("Frostpaw-Invite") && data.database.invites_paused(id.id).await {
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::MaintenanceError::InvitesPaused));
+    }
+
     if req.headers().contains_key("Frostpaw") {
         let auth_default = &HeaderValue::from_str("").unwrap();
         let auth = req.headers().get("Frostpaw-Auth").unwrap_or(auth_default);
@@ -1172,12 +1182,87 @@ async fn get_bot(req: HttpRequest, id: web::Path<models::FetchBotPath>) -> HttpR
     }
 }
 
+// Browse Bots
+#[get("/bots")]
+async fn browse_bots(req: HttpRequest, query: web::Query<models::BrowseQuery>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    let per_page = query.per_page.unwrap_or(25);
+
+    if !(1..=100).contains(&per_page) {
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+    }
+
+    // Only listed bots can be browsed
+    if !matches!(query.state, None | Some(models::State::Approved | models::State::Certified)) {
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+    }
+
+    let mut cursor = None;
+
+    if let Some(ref raw_cursor) = query.cursor {
+        // A cursor can only be used with the sort it was created for
+        match converters::decode_browse_cursor(raw_cursor) {
+            Some(decoded) if decoded.sort == query.sort.unwrap_or_default() => cursor = Some(decoded),
+            _ => {
+                return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+            }
+        }
+    }
+
+    let (results, next_cursor) = data.database.browse_bots(&query, cursor.as_ref(), per_page).await;
+
+    HttpResponse::Ok().json(models::Browse {
+        results,
+        next_cursor: next_cursor.map(|cursor| converters::encode_browse_cursor(&cursor)),
+    })
+}
+
 // Get Random Bot
 #[get("/random-bot")]
-async fn random_bot(req: HttpRequest) -> Json<models::IndexBot> {
+async fn random_bot(req: HttpRequest, info: web::Query<models::RandomQuery>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+    match data.database.random_bot(info.nsfw.unwrap_or(false)).await {
+        Some(bot) => HttpResponse::Ok().json(bot),
+        None => HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound)),
+    }
+}
+
+// Get Similar Bots
+#[get("/bots/{id}/similar")]
+async fn get_similar_bots(req: HttpRequest, id: web::Path<models::FetchBotPath>) -> HttpResponse {
     let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
-    let bot = data.database.random_bot().await;
-    Json(bot)
+
+    match data.database.get_similar_bots(id.id, 12).await {
+        Some(bots) => HttpResponse::Ok().json(bots),
+        None => HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound)),
+    }
+}
+
+#[get("/bots/{id}/stats/history")]
+async fn get_stats_history(
+    req: HttpRequest,
+    id: web::Path<models::FetchBotPath>,
+    query: web::Query<models::StatsHistoryQuery>,
+) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    let range = query.range.unwrap_or_default();
+
+    HttpResponse::Ok().json(data.database.get_stats_history(id.id, range).await)
+}
+
+#[get("/bots/{id}/uptime")]
+async fn get_bot_uptime(
+    req: HttpRequest,
+    id: web::Path<models::FetchBotPath>,
+    query: web::Query<models::UptimeQuery>,
+) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    let range = query.range.unwrap_or_default();
+
+    HttpResponse::Ok().json(data.database.get_bot_uptime(id.id, range).await)
 }
 
 /// Get Bot Settings
@@ -0,0 +1,157 @@
+// Owner management of bot events (promotions, maintenance and announcements)
+use crate::models;
+use actix_web::http::header::HeaderValue;
+use actix_web::{delete, get, patch, post, http, web, HttpRequest, HttpResponse};
+use log::error;
+
+fn event_check(event: &models::BotEventData) -> Result<(), models::BotEventError> {
+    let reason_len = event.reason.trim().chars().count();
+    if reason_len == 0 || reason_len > 1000 {
+        return Err(models::BotEventError::ReasonLength);
+    }
+
+    if event.css.chars().count() > 10000 {
+        return Err(models::BotEventError::CSSTooLong);
+    }
+
+    if let Some(ends_at) = event.ends_at {
+        if ends_at <= chrono::Utc::now() {
+            return Err(models::BotEventError::EndInPast);
+        }
+
+        if ends_at <= event.starts_at.unwrap_or_else(chrono::Utc::now) {
+            return Err(models::BotEventError::EndBeforeStart);
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the user is authorized and an owner of the bot
+async fn check_owner(req: &HttpRequest, data: &models::AppState, user_id: i64, bot_id: i64) -> Option<HttpResponse> {
+    let auth_default = &HeaderValue::from_str("").unwrap();
+    let auth = req
+        .headers()
+        .get("Authorization")
+        .unwrap_or(auth_default)
+        .to_str()
+        .unwrap();
+    if !data.database.authorize_user(user_id, auth).await {
+        error!("Bot Event Auth error");
+        return Some(HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden)));
+    }
+
+    if !data.database.is_bot_owner(bot_id, user_id).await {
+        return Some(HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden)));
+    }
+
+    None
+}
+
+#[get("/users/{user_id}/bots/{bot_id}/events")]
+async fn get_bot_events(req: HttpRequest, info: web::Path<models::GetUserBotPath>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    if let Some(resp) = check_owner(&req, data, info.user_id, info.bot_id).await {
+        return resp;
+    }
+
+    HttpResponse::Ok().json(data.database.get_bot_events(info.bot_id, true).await)
+}
+
+#[post("/users/{user_id}/bots/{bot_id}/events")]
+async fn add_bot_event(
+    req: HttpRequest,
+    info: web::Path<models::GetUserBotPath>,
+    event: web::Json<models::BotEventData>,
+) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    if let Some(resp) = check_owner(&req, data, info.user_id, info.bot_id).await {
+        return resp;
+    }
+
+    if let Err(err) = event_check(&event) {
+        return HttpResponse::BadRequest().json(models::APIResponse::err_small(&err));
+    }
+
+    if data.database.count_upcoming_bot_events(info.bot_id).await >= 10 {
+        return HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::BotEventError::TooManyEvents));
+    }
+
+    match data.database.add_bot_event(info.bot_id, &event).await {
+        Ok(id) => {
+            // Events starting right away are sent immediately
+            data.database.announce_bot_events().await;
+
+            HttpResponse::Ok().json(models::APIResponse {
+                done: true,
+                reason: None,
+                context: Some(id.to_string()),
+            })
+        },
+        Err(err) => HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::GenericError::SQLError(err))),
+    }
+}
+
+#[patch("/users/{user_id}/bots/{bot_id}/events/{event_id}")]
+async fn edit_bot_event(
+    req: HttpRequest,
+    info: web::Path<models::GetUserBotEventPath>,
+    event: web::Json<models::BotEventData>,
+) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    if let Some(resp) = check_owner(&req, data, info.user_id, info.bot_id).await {
+        return resp;
+    }
+
+    let event_id = match uuid::Uuid::parse_str(&info.event_id) {
+        Ok(id) => id,
+        Err(_) => return HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::BotEventError::InvalidEventId)),
+    };
+
+    let mut event = event.into_inner();
+
+    // The stored start is kept if none is given, the end must still be after it
+    if event.starts_at.is_none() {
+        match data.database.get_bot_event_start(info.bot_id, event_id).await {
+            Ok(Some(starts_at)) => event.starts_at = Some(starts_at),
+            Ok(None) => return HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound)),
+            Err(err) => return HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::GenericError::SQLError(err))),
+        }
+    }
+
+    if let Err(err) = event_check(&event) {
+        return HttpResponse::BadRequest().json(models::APIResponse::err_small(&err));
+    }
+
+    match data.database.edit_bot_event(info.bot_id, event_id, &event).await {
+        Ok(true) => {
+            data.database.announce_bot_events().await;
+            HttpResponse::Ok().json(models::APIResponse::ok())
+        },
+        Ok(false) => HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound)),
+        Err(err) => HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::GenericError::SQLError(err))),
+    }
+}
+
+#[delete("/users/{user_id}/bots/{bot_id}/events/{event_id}")]
+async fn delete_bot_event(req: HttpRequest, info: web::Path<models::GetUserBotEventPath>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    if let Some(resp) = check_owner(&req, data, info.user_id, info.bot_id).await {
+        return resp;
+    }
+
+    let event_id = match uuid::Uuid::parse_str(&info.event_id) {
+        Ok(id) => id,
+        Err(_) => return HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::BotEventError::InvalidEventId)),
+    };
+
+    match data.database.delete_bot_event(info.bot_id, event_id).await {
+        Ok(true) => HttpResponse::Ok().json(models::APIResponse::ok()),
+        Ok(false) => HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound)),
+        Err(err) => HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::GenericError::SQLError(err))),
+    }
+}
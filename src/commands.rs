@@ -1,9 +1,136 @@
 // Add, remove and delete commands from bots
 use crate::models;
+use crate::converters;
 use actix_web::http::header::HeaderValue;
-use actix_web::{delete, post, http, web, HttpRequest, HttpResponse};
+use actix_web::{delete, get, post, put, http, web, HttpRequest, HttpResponse};
 use log::{error, debug};
 
+fn check_name(command: &models::BotCommand) -> Result<(), models::CommandError> {
+    let name = &command.name;
+
+    // Context menu commands may use any characters but are limited to 32
+    if command.cmd_type == models::CommandType::ContextMenuCommand {
+        if name.trim().is_empty() || name.chars().count() > 32 {
+            return Err(models::CommandError::NameLength(name.clone()));
+        }
+        if name.chars().any(char::is_control) || name.trim() != name {
+            return Err(models::CommandError::InvalidName(name.clone()));
+        }
+        return Ok(());
+    }
+
+    if command.cmd_type == models::CommandType::PrefixCommand {
+        if name.trim().is_empty() || name.chars().count() > 64 {
+            return Err(models::CommandError::NameLength(name.clone()));
+        }
+        if name.chars().any(char::is_control) || name.trim() != name {
+            return Err(models::CommandError::InvalidName(name.clone()));
+        }
+        return Ok(());
+    }
+
+    // Slash commands may be subcommands (``config set prefix``)
+    let words: Vec<&str> = name.split(' ').collect();
+    if words.len() > 3 {
+        return Err(models::CommandError::InvalidName(name.clone()));
+    }
+
+    for word in words {
+        if word.is_empty() || word.chars().count() > 32 {
+            return Err(models::CommandError::NameLength(name.clone()));
+        }
+        let valid = word.chars().all(|c| {
+            (c.is_alphanumeric() && !c.is_uppercase()) || c == '-' || c == '_'
+        });
+        if !valid {
+            return Err(models::CommandError::InvalidName(name.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks a single command
+fn check_command(command: &models::BotCommand) -> Result<(), models::CommandError> {
+    check_name(command)?;
+
+    let name = &command.name;
+
+    let max_description = if command.cmd_type == models::CommandType::PrefixCommand {
+        500
+    } else {
+        100
+    };
+    let description_len = command.description.trim().chars().count();
+    if description_len == 0 || description_len > max_description {
+        return Err(models::CommandError::DescriptionLength(name.clone()));
+    }
+
+    if command.args.len() > 25 {
+        return Err(models::CommandError::TooManyArgs(name.clone()));
+    }
+
+    let mut arg_names = Vec::new();
+    for arg in &command.args {
+        if arg.name.is_empty() || arg.name.chars().count() > 32 || arg.name.contains(char::is_whitespace) {
+            return Err(models::CommandError::InvalidArg(name.clone(), arg.name.clone()));
+        }
+        if arg_names.contains(&&arg.name) {
+            return Err(models::CommandError::DuplicateArg(name.clone(), arg.name.clone()));
+        }
+        arg_names.push(&arg.name);
+
+        if arg.choices.len() > 25 || arg.choices.iter().any(|choice| choice.trim().is_empty() || choice.chars().count() > 100) {
+            return Err(models::CommandError::TooManyChoices(name.clone(), arg.name.clone()));
+        }
+    }
+
+    if command.examples.len() > 10 {
+        return Err(models::CommandError::TooManyExamples(name.clone()));
+    }
+    if command.examples.iter().any(|example| example.chars().count() > 200) {
+        return Err(models::CommandError::ExampleTooLong(name.clone()));
+    }
+
+    if command.notes.len() > 10 {
+        return Err(models::CommandError::TooManyNotes(name.clone()));
+    }
+    if command.notes.iter().any(|note| note.chars().count() > 200) {
+        return Err(models::CommandError::NoteTooLong(name.clone()));
+    }
+
+    if command.groups.len() > 5 || command.groups.iter().any(|group| group.trim().is_empty() || group.chars().count() > 32) {
+        return Err(models::CommandError::TooManyGroups(name.clone()));
+    }
+
+    // An empty link is treated as no link
+    if let Some(doc_link) = command.doc_link.as_ref().filter(|link| !link.is_empty()) {
+        if !doc_link.starts_with("https://") || doc_link.len() <= "https://".len() || doc_link.contains(char::is_whitespace) {
+            return Err(models::CommandError::InvalidDocLink(name.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks a list of commands, a command type and name pair may only be used once. 
+/// ``total`` is the number of commands the bot will have afterwards
+fn check_commands(commands: &[models::BotCommand], total: usize) -> Result<(), models::CommandError> {
+    if total > 250 {
+        return Err(models::CommandError::TooManyCommands(total));
+    }
+
+    let mut seen = Vec::new();
+    for command in commands {
+        check_command(command)?;
+        if seen.contains(&(command.cmd_type, &command.name)) {
+            return Err(models::CommandError::DuplicateCommand(command.name.clone()));
+        }
+        seen.push((command.cmd_type, &command.name));
+    }
+    Ok(())
+}
+
 #[post("/bots/{id}/commands")]
 async fn add_command(
     req: HttpRequest,
@@ -22,14 +149,18 @@ async fn add_command(
         .to_str()
         .unwrap();
     if data.database.authorize_bot(id, auth).await {
+        // Commands that already exist are updated and do not count towards the limit
+        let existing = data.database.get_bot_commands(id).await;
+        let new_commands = res.commands.iter()
+            .filter(|cmd| !existing.iter().any(|e| e.cmd_type == cmd.cmd_type && e.name == cmd.name))
+            .count();
+
+        if let Err(err) = check_commands(&res.commands, existing.len() + new_commands) {
+            return HttpResponse::BadRequest().json(models::APIResponse::err_small(&err)); 
+        }
+
         for command in &res.commands {
             debug!("Adding command: {}", command.name);
-            if command.name.is_empty() {
-                return HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::CommandError::CommandLengthError(command.name.clone()))); 
-            }
-            if command.description.is_empty() {
-                return HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::CommandError::CommandLengthError(command.name.clone()))); 
-            }
             let command_ret = data.database.add_command(id, command).await;
             if command_ret.is_err() {
                 return HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::GenericError::SQLError(command_ret.unwrap_err()))); 
@@ -90,3 +221,271 @@ async fn delete_commands(
     error!("Command delete auth error");
     HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden))
 }
+
+#[put("/bots/{id}/commands")]
+async fn sync_commands(
+    req: HttpRequest,
+    id: web::Path<models::FetchBotPath>,
+    res: web::Json<models::BotCommandVec>,
+) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+    let id = id.id;
+
+    // Check auth
+    let auth_default = &HeaderValue::from_str("").unwrap();
+    let auth = req
+        .headers()
+        .get("Authorization")
+        .unwrap_or(auth_default)
+        .to_str()
+        .unwrap();
+    if data.database.authorize_bot(id, auth).await {
+        if let Err(err) = check_commands(&res.commands, res.commands.len()) {
+            return HttpResponse::BadRequest().json(models::APIResponse::err_small(&err)); 
+        }
+
+        return match data.database.sync_commands(id, &res.commands).await {
+            Ok(summary) => {
+                debug!(
+                    "Synced commands for {}: {} added, {} updated, {} deleted", 
+                    id, summary.added.len(), summary.updated.len(), summary.deleted.len()
+                );
+                HttpResponse::Ok().json(summary)
+            },
+            Err(err) => HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::GenericError::SQLError(err))),
+        };
+    }
+    error!("Command sync auth error");
+    HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden))
+}
+
+#[post("/bots/{id}/commands/discord")]
+async fn import_discord_commands(
+    req: HttpRequest,
+    id: web::Path<models::FetchBotPath>,
+    query: web::Query<models::CommandImportQuery>,
+    res: web::Json<Vec<models::DiscordAppCommand>>,
+) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+    let id = id.id;
+
+    // Check auth
+    let auth_default = &HeaderValue::from_str("").unwrap();
+    let auth = req
+        .headers()
+        .get("Authorization")
+        .unwrap_or(auth_default)
+        .to_str()
+        .unwrap();
+    if data.database.authorize_bot(id, auth).await {
+        let imported: Vec<models::BotCommand> = res
+            .iter()
+            .flat_map(converters::discord_command_to_bot_commands)
+            .collect();
+
+        let replace = query.replace.unwrap_or(false);
+
+        /* Existing commands are kept unless they are overwritten by an imported one or
+        (when replacing) are slash commands. Syncing this list then only touches what changed
+        */
+        let mut commands: Vec<models::BotCommand> = data
+            .database
+            .get_bot_commands(id)
+            .await
+            .into_iter()
+            .filter(|existing| {
+                !(replace && existing.cmd_type != models::CommandType::PrefixCommand)
+                    && !imported.iter().any(|cmd| cmd.cmd_type == existing.cmd_type && cmd.name == existing.name)
+            })
+            .collect();
+
+        let total = commands.len() + imported.len();
+
+        if let Err(err) = check_commands(&imported, total) {
+            return HttpResponse::BadRequest().json(models::APIResponse::err_small(&err)); 
+        }
+
+        commands.extend(imported);
+
+        return match data.database.sync_commands(id, &commands).await {
+            Ok(summary) => HttpResponse::Ok().json(summary),
+            Err(err) => HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::GenericError::SQLError(err))),
+        };
+    }
+    error!("Command import auth error");
+    HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden))
+}
+
+#[get("/commands/search")]
+async fn search_commands(req: HttpRequest, info: web::Query<models::CommandSearchQuery>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    let page = info.page.unwrap_or(1);
+    let per_page = info.per_page.unwrap_or(12);
+
+    if page < 1 || !(1..=50).contains(&per_page) {
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+    }
+
+    let offset = match (page - 1).checked_mul(per_page) {
+        Some(offset) => offset,
+        None => return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields)),
+    };
+
+    let (results, total) = data.database.search_commands(&info, per_page, offset).await;
+
+    HttpResponse::Ok().json(models::CommandSearch {
+        results,
+        total,
+        per_page,
+        from: offset,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(cmd_type: models::CommandType, name: &str) -> models::BotCommand {
+        models::BotCommand {
+            cmd_type,
+            name: name.to_string(),
+            description: "Does something".to_string(),
+            groups: vec!["Default".to_string()],
+            ..models::BotCommand::default()
+        }
+    }
+
+    fn slash(name: &str) -> models::BotCommand {
+        command(models::CommandType::SlashCommandGlobal, name)
+    }
+
+    #[test]
+    fn slash_names() {
+        assert!(check_command(&slash("ping")).is_ok());
+        assert!(check_command(&slash("config set prefix")).is_ok());
+        assert!(check_command(&slash("a-b_c")).is_ok());
+        assert!(matches!(check_command(&slash("a b c d")), Err(models::CommandError::InvalidName(_))));
+        assert!(matches!(check_command(&slash("config  set")), Err(models::CommandError::NameLength(_))));
+        assert!(matches!(check_command(&slash(&"a".repeat(33))), Err(models::CommandError::NameLength(_))));
+        assert!(check_command(&slash(&"a".repeat(32))).is_ok());
+    }
+
+    #[test]
+    fn slash_charset() {
+        assert!(matches!(check_command(&slash("Ping")), Err(models::CommandError::InvalidName(_))));
+        assert!(matches!(check_command(&slash("ping!")), Err(models::CommandError::InvalidName(_))));
+        // Lowercase letters of other scripts are allowed, just like on Discord
+        assert!(check_command(&slash("привет")).is_ok());
+        assert!(check_command(&slash("設定")).is_ok());
+    }
+
+    #[test]
+    fn other_names() {
+        let prefix = command(models::CommandType::PrefixCommand, "Say Hello!");
+        assert!(check_command(&prefix).is_ok());
+
+        let prefix = command(models::CommandType::PrefixCommand, &"a".repeat(65));
+        assert!(matches!(check_command(&prefix), Err(models::CommandError::NameLength(_))));
+
+        let context_menu = command(models::CommandType::ContextMenuCommand, "Report Message");
+        assert!(check_command(&context_menu).is_ok());
+
+        let context_menu = command(models::CommandType::ContextMenuCommand, " Report");
+        assert!(matches!(check_command(&context_menu), Err(models::CommandError::InvalidName(_))));
+
+        let context_menu = command(models::CommandType::ContextMenuCommand, "Report\n");
+        assert!(matches!(check_command(&context_menu), Err(models::CommandError::InvalidName(_))));
+    }
+
+    #[test]
+    fn doc_link() {
+        let with_link = |link: &str| models::BotCommand {
+            doc_link: Some(link.to_string()),
+            ..slash("ping")
+        };
+
+        assert!(check_command(&with_link("https://docs.example.com/ping")).is_ok());
+        // An empty link is no link
+        assert!(check_command(&with_link("")).is_ok());
+        assert!(matches!(check_command(&with_link("http://docs.example.com")), Err(models::CommandError::InvalidDocLink(_))));
+        assert!(matches!(check_command(&with_link("https://")), Err(models::CommandError::InvalidDocLink(_))));
+        assert!(matches!(check_command(&with_link("https://docs.example.com/a b")), Err(models::CommandError::InvalidDocLink(_))));
+    }
+
+    #[test]
+    fn limits() {
+        let cmd = models::BotCommand {
+            description: "a".repeat(101),
+            ..slash("ping")
+        };
+        assert!(matches!(check_command(&cmd), Err(models::CommandError::DescriptionLength(_))));
+
+        let cmd = models::BotCommand {
+            notes: vec!["note".to_string(); 11],
+            ..slash("ping")
+        };
+        assert!(matches!(check_command(&cmd), Err(models::CommandError::TooManyNotes(_))));
+
+        let cmd = models::BotCommand {
+            notes: vec!["a".repeat(201)],
+            ..slash("ping")
+        };
+        assert!(matches!(check_command(&cmd), Err(models::CommandError::NoteTooLong(_))));
+
+        let cmd = models::BotCommand {
+            groups: vec!["a".repeat(33)],
+            ..slash("ping")
+        };
+        assert!(matches!(check_command(&cmd), Err(models::CommandError::TooManyGroups(_))));
+
+        let cmd = models::BotCommand {
+            examples: vec!["ping".to_string(); 11],
+            ..slash("ping")
+        };
+        assert!(matches!(check_command(&cmd), Err(models::CommandError::TooManyExamples(_))));
+
+        let commands: Vec<_> = (0..251).map(|i| slash(&format!("cmd{}", i))).collect();
+        assert!(matches!(check_commands(&commands, commands.len()), Err(models::CommandError::TooManyCommands(251))));
+
+        let commands = vec![slash("ping"), slash("ping")];
+        assert!(matches!(check_commands(&commands, 2), Err(models::CommandError::DuplicateCommand(_))));
+    }
+
+    #[test]
+    fn discord_import_fits_limits() {
+        // As large as Discord allows: 25 described options and a subcommand group label of 65
+        let options: Vec<_> = (0..25)
+            .map(|i| serde_json::json!({
+                "type": 3,
+                "name": format!("{:0>32}", i),
+                "description": "d".repeat(100),
+            }))
+            .collect();
+
+        let command: models::DiscordAppCommand = serde_json::from_value(serde_json::json!({
+            "type": 1,
+            "name": "a".repeat(32),
+            "description": "A command",
+            "options": [{
+                "type": 2,
+                "name": "b".repeat(32),
+                "description": "A group",
+                "options": [{
+                    "type": 1,
+                    "name": "c".repeat(32),
+                    "description": "A subcommand",
+                    "options": options,
+                }],
+            }],
+        }))
+        .unwrap();
+
+        let commands = converters::discord_command_to_bot_commands(&command);
+
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].groups, vec!["b".repeat(32)]);
+        assert_eq!(commands[0].notes.len(), 10);
+        assert!(check_commands(&commands, commands.len()).is_ok());
+    }
+}
@@ -88,6 +88,82 @@ pub fn create_token(length: usize) -> String {
         .collect()
 }
 
+/// Encodes a review cursor. Cursors are opaque to clients
+pub fn encode_review_cursor(cursor: &models::ReviewCursor) -> String {
+    base64::encode_config(
+        format!("{}|{}|{}", cursor.sort as i32, cursor.key, cursor.id),
+        base64::URL_SAFE_NO_PAD,
+    )
+}
+
+pub fn decode_review_cursor(cursor: &str) -> Option<models::ReviewCursor> {
+    let decoded = base64::decode_config(cursor, base64::URL_SAFE_NO_PAD).ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+
+    let mut split = decoded.split('|');
+
+    let sort = split.next()?.parse::<i32>().ok()?;
+    let key = split.next()?.parse::<bigdecimal::BigDecimal>().ok()?;
+    let id = uuid::Uuid::parse_str(split.next()?).ok()?;
+
+    Some(models::ReviewCursor {
+        sort: models::ReviewSort::try_from(sort).ok()?,
+        key,
+        id,
+    })
+}
+
+pub fn encode_browse_cursor(cursor: &models::BrowseCursor) -> String {
+    base64::encode_config(
+        format!("{}|{}|{}|{}", cursor.sort as i32, cursor.key, cursor.id, cursor.now),
+        base64::URL_SAFE_NO_PAD,
+    )
+}
+
+pub fn decode_browse_cursor(cursor: &str) -> Option<models::BrowseCursor> {
+    let decoded = base64::decode_config(cursor, base64::URL_SAFE_NO_PAD).ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+
+    let mut split = decoded.split('|');
+
+    let sort = split.next()?.parse::<i32>().ok()?;
+    let key = split.next()?.parse::<bigdecimal::BigDecimal>().ok()?;
+    let id = split.next()?.parse::<i64>().ok()?;
+    let now = split.next()?.parse::<i64>().ok()?;
+
+    Some(models::BrowseCursor {
+        sort: models::BrowseSort::try_from(sort).ok()?,
+        key,
+        id,
+        now,
+    })
+}
+
+/// Converts a search query to a tsquery where every word is prefix matched
+/// 
+/// Only alphanumeric characters are kept so the result is always a valid tsquery
+pub fn to_prefix_tsquery(query: &str) -> String {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(10)
+        .map(|word| word.to_lowercase() + ":*")
+        .collect::<Vec<String>>()
+        .join(" & ")
+}
+
+/// Escapes a ts_headline snippet and replaces our markers with ``<mark>`` tags
+pub fn highlight_snippet(snippet: &str) -> String {
+    snippet
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+        .replace("[[[", "<mark>")
+        .replace("]]]", "</mark>")
+}
+
 pub fn flags_check(flag_list: &[i32], flag_vec: Vec<i32>) -> bool {
     for flag in flag_vec {
         if flag_list.contains(&flag) {
@@ -186,3 +262,154 @@ pub async fn send_vote_webhook(
         break;
     }
 }
+
+/// Turns the non-subcommand options of a Discord command into args
+fn discord_option_args(options: &[models::DiscordAppCommandOption]) -> Vec<models::CommandArg> {
+    options
+        .iter()
+        .filter(|option| option.option_type > 2)
+        .map(|option| models::CommandArg {
+            name: option.name.clone(),
+            arg_type: match option.option_type {
+                4 => models::CommandArgType::Integer,
+                5 => models::CommandArgType::Boolean,
+                6 => models::CommandArgType::User,
+                7 => models::CommandArgType::Channel,
+                8 => models::CommandArgType::Role,
+                9 => models::CommandArgType::Mentionable,
+                10 => models::CommandArgType::Number,
+                11 => models::CommandArgType::Attachment,
+                _ => models::CommandArgType::String,
+            },
+            required: option.required.unwrap_or(false),
+            choices: option
+                .choices
+                .iter()
+                .flatten()
+                .map(|choice| choice.name.clone())
+                .collect(),
+        })
+        .collect()
+}
+
+/// Turns the option descriptions of a Discord command into notes. Discord allows 25 options
+/// but commands may only have 10 notes of up to 200 characters, so descriptions are folded
+/// into as few notes as possible and any that do not fit are left out
+fn discord_option_notes(options: &[models::DiscordAppCommandOption]) -> Vec<String> {
+    let mut notes: Vec<String> = Vec::new();
+
+    for option in options.iter().filter(|option| option.option_type > 2 && !option.description.is_empty()) {
+        let line = format!("{}: {}", option.name, option.description);
+
+        if let Some(note) = notes.last_mut() {
+            if note.chars().count() + 2 + line.chars().count() <= 200 {
+                note.push_str("; ");
+                note.push_str(&line);
+                continue;
+            }
+        }
+
+        if notes.len() == 10 {
+            break;
+        }
+
+        notes.push(line.chars().take(200).collect());
+    }
+
+    notes
+}
+
+/// The group of a subcommand. Discord allows 32 characters for each of ``command group`` but
+/// groups may only have 32 characters in total, longer ones use only the innermost name
+fn discord_group(path: &[&str]) -> String {
+    let group = path.join(" ");
+
+    if group.chars().count() <= 32 {
+        group
+    } else {
+        path.last().map(|name| name.chars().take(32).collect()).unwrap_or_default()
+    }
+}
+
+/// Converts a Discord application command to commands. Every subcommand becomes its own
+/// command named after its full path (such as ``config set prefix``) and grouped under
+/// its parent command or subcommand group
+pub fn discord_command_to_bot_commands(command: &models::DiscordAppCommand) -> Vec<models::BotCommand> {
+    let cmd_type = if command.guild_id.is_some() {
+        models::CommandType::SlashCommandGuild
+    } else {
+        models::CommandType::SlashCommandGlobal
+    };
+
+    let nsfw = command.nsfw.unwrap_or(false);
+
+    // Context menu commands have no description or options
+    let context_menu = match command.command_type.unwrap_or(1) {
+        2 => Some("User context menu command"),
+        3 => Some("Message context menu command"),
+        _ => None,
+    };
+
+    if let Some(description) = context_menu {
+        return vec![models::BotCommand {
+            cmd_type: models::CommandType::ContextMenuCommand,
+            name: command.name.clone(),
+            description: description.to_string(),
+            groups: vec!["Context Menu".to_string()],
+            nsfw,
+            ..models::BotCommand::default()
+        }];
+    }
+
+    let options = command.options.as_deref().unwrap_or_default();
+
+    let subcommand = |path: &[&str], sub: &models::DiscordAppCommandOption| {
+        let sub_options = sub.options.as_deref().unwrap_or_default();
+        models::BotCommand {
+            cmd_type,
+            name: format!("{} {}", path.join(" "), sub.name),
+            description: sub.description.clone(),
+            args: discord_option_args(sub_options),
+            notes: discord_option_notes(sub_options),
+            groups: vec![discord_group(path)],
+            nsfw,
+            ..models::BotCommand::default()
+        }
+    };
+
+    let mut commands = Vec::new();
+    let mut has_subcommands = false;
+
+    for option in options {
+        match option.option_type {
+            1 => {
+                has_subcommands = true;
+                commands.push(subcommand(&[command.name.as_str()], option));
+            }
+            2 => {
+                has_subcommands = true;
+                for sub in option.options.as_deref().unwrap_or_default() {
+                    if sub.option_type == 1 {
+                        commands.push(subcommand(&[command.name.as_str(), option.name.as_str()], sub));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !has_subcommands {
+        commands.push(models::BotCommand {
+            cmd_type,
+            name: command.name.clone(),
+            description: command.description.clone(),
+            args: discord_option_args(options),
+            notes: discord_option_notes(options),
+            groups: vec!["Default".to_string()],
+            nsfw,
+            ..models::BotCommand::default()
+        });
+    }
+
+    commands
+}
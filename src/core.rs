@@ -1,6 +1,7 @@
 // A core endpoint is one that is absolutely essential for proper list functions
 use crate::models;
 use actix_web::{get, http, web, web::Json, HttpRequest, HttpResponse};
+use actix_web::http::header::HeaderValue;
 use strum::IntoEnumIterator;
 use std::sync::Arc;
 
@@ -9,38 +10,113 @@ async fn ping(_req: HttpRequest) -> HttpResponse {
 	HttpResponse::Ok().finish()
 }
 
-#[get("/index")]
-async fn index(req: HttpRequest, info: web::Query<models::IndexQuery>) -> HttpResponse {
-    let mut index = models::Index::new();
-
-    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+/// Returns the (cached) index for a target type. Indexes excluding the votes of a user
+/// are per user and never cached
+async fn get_index(data: &models::AppState, target_type: models::TargetType, opts: models::IndexOptions) -> Arc<models::Index> {
+    let cacheable = opts.exclude_voted_by.is_none();
 
-    let cache = data.database.index_cache.get(&info.target_type);
-        
-    if cache.is_some() {
-        return HttpResponse::Ok().json(cache.unwrap());
+    if cacheable {
+        if let Some(index) = data.database.index_cache.get(&(target_type, opts)) {
+            return index;
+        }
     }
 
-    let index = Arc::new(if info.target_type == models::TargetType::Bot {
-        index.top_voted = data.database.index_bots(models::State::Approved).await;
-        index.certified = data.database.index_bots(models::State::Certified).await;
+    let mut index = models::Index::new();
+
+    let index = Arc::new(if target_type == models::TargetType::Bot {
+        index.top_voted = data.database.index_bots(models::State::Approved, &opts).await;
+        index.certified = data.database.index_bots(models::State::Certified, &opts).await;
         index.tags = data.database.bot_list_tags().await;
-        index.new = data.database.index_new_bots().await;
+        index.new = data.database.index_new_bots(&opts).await;
         index.features = data.database.bot_features().await;
+        index.trending = models::Trending {
+            day: data.database.index_trending_bots(24, &opts).await,
+            week: data.database.index_trending_bots(24 * 7, &opts).await,
+        };
 
         index
     } else {
-        index.top_voted = data.database.index_servers(models::State::Approved).await;
-        index.certified = data.database.index_servers(models::State::Certified).await;
-        index.new = data.database.index_new_servers().await;
+        index.top_voted = data.database.index_servers(models::State::Approved, &opts).await;
+        index.certified = data.database.index_servers(models::State::Certified, &opts).await;
+        index.new = data.database.index_new_servers(&opts).await;
         index.tags = data.database.server_list_tags().await;
+        index.trending = models::Trending {
+            day: data.database.index_trending_servers(24, &opts).await,
+            week: data.database.index_trending_servers(24 * 7, &opts).await,
+        };
 
         index 
-   });
-    data.database.index_cache.insert(info.target_type, index.clone()).await;
+    });
+    if cacheable {
+        data.database.index_cache.insert((target_type, opts), index.clone()).await;
+    }
+    index
+}
+
+#[get("/index")]
+async fn index(req: HttpRequest, info: web::Query<models::IndexQuery>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    let limit = info.limit.unwrap_or(12);
+
+    if !(1..=50).contains(&limit) {
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+    }
+
+    let exclude_voted = info.exclude_voted.unwrap_or(false);
+
+    if exclude_voted && info.user_id.is_none() {
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+    }
+
+    if let Some(user_id) = info.user_id {
+        let auth_default = &HeaderValue::from_str("").unwrap();
+        let auth = req
+            .headers()
+            .get("Authorization")
+            .unwrap_or(auth_default)
+            .to_str()
+            .unwrap();
+        if !data.database.authorize_user(user_id, auth).await {
+            return HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden));
+        }
+    }
+
+    let nsfw = match (info.nsfw, info.user_id) {
+        (Some(nsfw), _) => nsfw,
+        (None, Some(user_id)) => data.database.get_user_show_nsfw(user_id).await,
+        (None, None) => true,
+    };
+
+    let opts = models::IndexOptions {
+        limit,
+        nsfw,
+        exclude_voted_by: if exclude_voted { info.user_id } else { None },
+    };
+
+    let index = get_index(data, info.target_type, opts).await;
+
+    // Recommendations are per user so they are never cached as part of the index
+    if let Some(user_id) = info.user_id {
+        if info.target_type == models::TargetType::Bot {
+            let mut index = (*index).clone();
+            index.recommended = data.database.get_recommended_bots(user_id, limit).await;
+            return HttpResponse::Ok().json(index);
+        }
+    }
+
     HttpResponse::Ok().json(index)
 }
 
+#[get("/trending")]
+async fn trending(req: HttpRequest, info: web::Query<models::TrendingQuery>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    let index = get_index(data, info.target_type, models::IndexOptions::default()).await;
+
+    HttpResponse::Ok().json(&index.trending)
+}
+
 #[get("/code/{vanity}")]
 async fn resolve_vanity(req: HttpRequest, code: web::Path<String>) -> HttpResponse {
     if code.starts_with('_') {
@@ -84,19 +160,90 @@ async fn search_list(req: HttpRequest, info: web::Query<models::SearchQuery>) ->
 
     let search = info.into_inner();
 
-    let search_key = format!("{query}-{gc_from}-{gc_to}", query = search.q, gc_from = search.gc_from, gc_to = search.gc_to);
+    let page = search.page.unwrap_or(1).max(1);
+    let per_page = search.per_page.unwrap_or(12).clamp(1, 50);
+
+    let offset = match (page - 1).checked_mul(per_page) {
+        Some(offset) => offset,
+        None => return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields)),
+    };
+
+    // Every field of the query affects the results
+    let search_key = serde_json::to_string(&search).unwrap_or_default();
 
     let cached_resp = data.database.search_cache.get(&search_key);
     match cached_resp {
         Some(resp) => HttpResponse::Ok().json(resp),
         None => {
-            let search_resp = Arc::new(data.database.search(search).await);
+            let search_resp = Arc::new(data.database.search(search, per_page, offset).await);
             data.database.search_cache.insert(search_key, search_resp.clone()).await;
             HttpResponse::Ok().json(search_resp)
         }
     }
 }
 
+#[get("/search/profiles")]
+async fn search_profiles(req: HttpRequest, info: web::Query<models::PagedSearchQuery>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    let page = info.page.unwrap_or(1);
+    let per_page = info.per_page.unwrap_or(12);
+
+    if page < 1 || !(1..=50).contains(&per_page) {
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+    }
+
+    let offset = match (page - 1).checked_mul(per_page) {
+        Some(offset) => offset,
+        None => return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields)),
+    };
+
+    let (profiles, total) = data.database.search_profiles(&info.q, per_page, offset).await;
+
+    HttpResponse::Ok().json(models::ProfileSearch {
+        profiles,
+        total,
+        per_page,
+        from: offset,
+    })
+}
+
+#[get("/search/packs")]
+async fn search_packs(req: HttpRequest, info: web::Query<models::PagedSearchQuery>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    let page = info.page.unwrap_or(1);
+    let per_page = info.per_page.unwrap_or(12);
+
+    if page < 1 || !(1..=50).contains(&per_page) {
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+    }
+
+    let offset = match (page - 1).checked_mul(per_page) {
+        Some(offset) => offset,
+        None => return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields)),
+    };
+
+    let (packs, total) = data.database.search_packs(&info.q, per_page, offset).await;
+
+    HttpResponse::Ok().json(models::PackSearch {
+        packs,
+        total,
+        per_page,
+        from: offset,
+    })
+}
+
+/// Search suggestions for the search box. This uses an in-memory index and never touches the database
+#[get("/search/suggest")]
+async fn search_suggest(req: HttpRequest, info: web::Query<models::SuggestQuery>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    let limit = info.limit.unwrap_or(8).clamp(1, 25);
+
+    HttpResponse::Ok().json(data.database.search_suggest(&info.q, limit))
+}
+
 // Search Tags
 #[get("/search-tags")]
 async fn search_tags(
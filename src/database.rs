@@ -9,7 +9,7 @@ use deadpool_redis::redis::AsyncCommands;
 use deadpool_redis::{Config, Runtime};
 use indexmap::IndexMap;
 use indexmap::indexmap;
-use log::{debug, error};
+use log::{debug, error, info};
 use serde::Serialize;
 use serde_json::json;
 use serenity::model::prelude::*;
@@ -22,6 +22,77 @@ use std::sync::Arc;
 use std::time::Duration;
 use moka::future::Cache;
 
+/// How deep review reply threads are loaded
+const MAX_REVIEW_REPLY_DEPTH: i64 = 5;
+
+/// How many replies are loaded for a single review
+const MAX_REVIEW_REPLIES: i64 = 25;
+
+/// Splits a ``|`` seperated list from a query string, empty lists become ``None``
+fn split_list(list: &Option<String>) -> Option<Vec<String>> {
+    let items: Vec<String> = list
+        .as_deref()?
+        .split('|')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect();
+
+    if items.is_empty() {
+        None
+    } else {
+        Some(items)
+    }
+}
+
+/// A prefix index of names and vanities used for search suggestions
+#[derive(Default)]
+pub struct SuggestIndex {
+    /// Sorted by key (a lowercased name or vanity). Every entry also has a score (votes etc.)
+    entries: Vec<(String, i64, models::Suggestion)>,
+}
+
+impl SuggestIndex {
+    fn new(mut entries: Vec<(String, i64, models::Suggestion)>) -> Self {
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        SuggestIndex { entries }
+    }
+
+    /// Returns the highest scoring suggestions starting with ``prefix``
+    pub fn lookup(&self, prefix: &str, limit: usize) -> Vec<models::Suggestion> {
+        let prefix = prefix.trim().to_lowercase();
+
+        if prefix.is_empty() {
+            return Vec::new();
+        }
+
+        let start = self.entries.partition_point(|entry| entry.0.as_str() < prefix.as_str());
+
+        let mut matches: Vec<&(String, i64, models::Suggestion)> = self.entries[start..]
+            .iter()
+            .take_while(|entry| entry.0.starts_with(&prefix))
+            .collect();
+
+        matches.sort_by(|a, b| b.1.cmp(&a.1));
+
+        let mut suggestions: Vec<models::Suggestion> = Vec::new();
+
+        for (_, _, suggestion) in matches {
+            // A entity may match on both its name and vanity
+            if suggestions.iter().any(|s| s.id == suggestion.id && s.suggestion_type == suggestion.suggestion_type) {
+                continue;
+            }
+
+            suggestions.push(suggestion.clone());
+
+            if suggestions.len() >= limit {
+                break;
+            }
+        }
+
+        suggestions
+    }
+}
+
 #[derive(Clone)]
 pub struct Database {
     pool: PgPool,
@@ -34,9 +105,12 @@ pub struct Database {
     // Our moka caches
     pub bot_cache: Cache<i64, Arc<models::Bot>>,
     pub server_cache: Cache<i64, Arc<models::Server>>,
-    pub index_cache: Cache<models::TargetType, Arc<models::Index>>,
+    pub index_cache: Cache<(models::TargetType, models::IndexOptions), Arc<models::Index>>,
     pub search_cache: Cache<String, Arc<models::Search>>,
+    pub review_stats_cache: Cache<(i64, models::TargetType), Arc<models::ReviewStats>>,
     pub client_data: Cache<String, Arc<models::FrostpawLogin>>,
+    /// Refreshed periodically by a background task
+    pub suggest_index: Arc<std::sync::RwLock<Arc<SuggestIndex>>>,
 }
 
 impl Database {
@@ -83,6 +157,13 @@ impl Database {
                 .time_to_idle(Duration::from_secs(45))
                 // Create the cache.
                 .build(),
+            review_stats_cache: Cache::builder()
+                // Time to live (TTL): 10 minutes
+                .time_to_live(Duration::from_secs(10 * 60))
+                // Time to idle (TTI):  5 minutes
+                .time_to_idle(Duration::from_secs(5 * 60))
+                // Create the cache.
+                .build(),
             client_data: Cache::builder()
                 // Time to live (TTL): 15 minutes
                 .time_to_live(Duration::from_secs(15 * 60))
@@ -90,6 +171,7 @@ impl Database {
                 .time_to_idle(Duration::from_secs(15 * 60))
                 // Create the cache.
                 .build(),
+            suggest_index: Arc::new(std::sync::RwLock::new(Arc::new(SuggestIndex::default()))),
             discord_main,
             discord_server,
         }
@@ -110,6 +192,26 @@ impl Database {
         conn.set_ex(&key, 0, identifier as usize).await.unwrap_or_else(|_| 0);
     }
 
+    /// Counts a request in a fixed window rate limit bucket. Returns the number of requests in
+    /// the current window and the seconds until it resets, or ``None`` if redis is unavailable
+    pub async fn hit_ratelimit(&self, bucket: &str, per_secs: u64) -> Option<(u32, i64)> {
+        let mut conn = self.redis.get().await.ok()?;
+        let key = format!("rlbucket:{}", bucket);
+
+        // The window starts (with its expiry) on the first request, all in one transaction so a
+        // bucket can never be left without an expiry
+        let (count, ttl): (u32, i64) = deadpool_redis::redis::pipe()
+            .atomic()
+            .cmd("SET").arg(&key).arg(0).arg("EX").arg(per_secs).arg("NX").ignore()
+            .incr(&key, 1)
+            .ttl(&key)
+            .query_async(&mut conn)
+            .await
+            .ok()?;
+
+        Some((count, ttl))
+    }
+
     pub async fn add_refresh_token(&self, client_id: &str, id: i64) -> String {
         let refresh_token = converters::create_token(128);
         sqlx::query!(
@@ -190,6 +292,27 @@ impl Database {
         self.pool.clone()
     }
 
+    /// Whether the user is staff with at least the given permission level (according to baypaw).
+    /// If baypaw is down, nobody is staff
+    pub async fn is_staff(&self, user_id: i64, min_perm: f32) -> bool {
+        let res = self.requests
+            .get(format!("http://127.0.0.1:1234/perms/{}", user_id))
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await;
+
+        match res {
+            Ok(res) => res
+                .json::<models::StaffPerm>()
+                .await
+                .map_or(false, |perm| perm.perm >= min_perm),
+            Err(err) => {
+                error!("Error getting perms of {}: {}", user_id, err);
+                false
+            }
+        }
+    }
+
     pub async fn get_user(&self, user_id: i64) -> models::User {
         // First check cache
         let mut conn = self.redis.get().await.unwrap();
@@ -278,12 +401,19 @@ impl Database {
         user
     }
 
-    pub async fn index_bots(&self, state: models::State) -> Vec<models::IndexBot> {
+    pub async fn index_bots(&self, state: models::State, opts: &models::IndexOptions) -> Vec<models::IndexBot> {
         let mut bots: Vec<models::IndexBot> = Vec::new();
         let rows = sqlx::query!(
             "SELECT bot_id, created_at, flags, description, banner_card, state, votes, guild_count 
-            FROM bots WHERE state = $1 ORDER BY votes DESC LIMIT 12",
-            state as i32
+            FROM bots WHERE state = $1
+            AND ($2::boolean OR NOT ($3 = ANY(flags)))
+            AND ($4::bigint IS NULL OR bot_id NOT IN (SELECT bot_id FROM bot_voters WHERE user_id = $4))
+            ORDER BY votes DESC LIMIT $5",
+            state as i32,
+            opts.nsfw,
+            models::Flags::NSFW as i32,
+            opts.exclude_voted_by,
+            opts.limit,
         )
             .fetch_all(&self.pool)
             .await
@@ -299,6 +429,8 @@ impl Database {
                 votes: row.votes.unwrap_or(0),
                 flags: row.flags,
                 user: self.get_user(row.bot_id).await,
+                review_stats: Some(self.get_review_stats(row.bot_id, models::TargetType::Bot).await),
+                search_meta: None,
                 created_at: row.created_at,
             };
             bots.push(bot);
@@ -306,6 +438,156 @@ impl Database {
         bots
     }
 
+    /// Recomputes the similar bots of every bot using co-voting, shared tags and shared features
+    pub async fn compute_similar_bots(&self) {
+        let mut tx = match self.pool.begin().await {
+            Ok(tx) => tx,
+            Err(err) => {
+                error!("Could not compute similar bots: {}", err);
+                return;
+            }
+        };
+
+        if let Err(err) = sqlx::query!("DELETE FROM bot_similar").execute(&mut tx).await {
+            error!("Could not compute similar bots: {}", err);
+            return;
+        }
+
+        /* Users voting for both bots count the most (logarithmically so a few huge bots do not
+        dominate), then every shared tag and finally every shared feature. Only the best 20
+        are kept for every bot
+        */
+        let res = sqlx::query!(
+            "WITH listed AS (
+                SELECT bot_id, COALESCE(features, '{}') AS features FROM bots WHERE state = $1 OR state = $2
+            ), pairs AS (
+                SELECT a.bot_id, b.bot_id AS similar_bot_id, (3 * LN(1 + COUNT(DISTINCT a.user_id)))::double precision AS score
+                FROM bot_voters a INNER JOIN bot_voters b ON a.user_id = b.user_id AND a.bot_id != b.bot_id
+                GROUP BY a.bot_id, b.bot_id
+                UNION ALL
+                SELECT a.bot_id, b.bot_id AS similar_bot_id, COUNT(*)::double precision AS score
+                FROM bot_tags a INNER JOIN bot_tags b ON a.tag = b.tag AND a.bot_id != b.bot_id
+                GROUP BY a.bot_id, b.bot_id
+                UNION ALL
+                SELECT a.bot_id, b.bot_id AS similar_bot_id, 
+                (0.5 * cardinality(ARRAY(SELECT unnest(a.features) INTERSECT SELECT unnest(b.features))))::double precision AS score
+                FROM listed a INNER JOIN listed b ON a.bot_id != b.bot_id AND a.features && b.features
+            ), scored AS (
+                SELECT pairs.bot_id, pairs.similar_bot_id, SUM(pairs.score) AS score,
+                ROW_NUMBER() OVER (PARTITION BY pairs.bot_id ORDER BY SUM(pairs.score) DESC) AS position
+                FROM pairs
+                INNER JOIN listed a ON a.bot_id = pairs.bot_id
+                INNER JOIN listed b ON b.bot_id = pairs.similar_bot_id
+                GROUP BY pairs.bot_id, pairs.similar_bot_id
+            )
+            INSERT INTO bot_similar (bot_id, similar_bot_id, score) 
+            SELECT bot_id, similar_bot_id, score FROM scored WHERE position <= 20",
+            models::State::Approved as i32,
+            models::State::Certified as i32,
+        )
+        .execute(&mut tx)
+        .await;
+
+        if let Err(err) = res {
+            error!("Could not compute similar bots: {}", err);
+            return;
+        }
+
+        if let Err(err) = tx.commit().await {
+            error!("Could not compute similar bots: {}", err);
+        }
+    }
+
+    /// Returns None if the bot does not exist
+    pub async fn get_similar_bots(&self, bot_id: i64, limit: i64) -> Option<Vec<models::IndexBot>> {
+        let exists = sqlx::query!("SELECT bot_id FROM bots WHERE bot_id = $1", bot_id)
+            .fetch_optional(&self.pool)
+            .await
+            .unwrap_or_default();
+
+        exists.as_ref()?;
+
+        let rows = sqlx::query!(
+            "SELECT bots.bot_id, bots.created_at, bots.flags, bots.description, 
+            bots.banner_card, bots.state, bots.votes, bots.guild_count FROM bot_similar 
+            INNER JOIN bots ON bots.bot_id = bot_similar.similar_bot_id
+            WHERE bot_similar.bot_id = $1 AND (bots.state = $2 OR bots.state = $3) 
+            ORDER BY bot_similar.score DESC LIMIT $4",
+            bot_id,
+            models::State::Approved as i32,
+            models::State::Certified as i32,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        let mut bots = Vec::new();
+
+        for row in rows {
+            bots.push(models::IndexBot {
+                guild_count: row.guild_count.unwrap_or(0),
+                description: row.description,
+                banner: row.banner_card.unwrap_or_else(|| {
+                    "https://api.fateslist.xyz/static/assets/prod/banner.webp".to_string()
+                }),
+                state: models::State::try_from(row.state).unwrap_or(models::State::Approved),
+                votes: row.votes.unwrap_or(0),
+                flags: row.flags,
+                user: self.get_user(row.bot_id).await,
+                review_stats: Some(self.get_review_stats(row.bot_id, models::TargetType::Bot).await),
+                search_meta: None,
+                created_at: row.created_at,
+            });
+        }
+
+        Some(bots)
+    }
+
+    /// Recommends bots similar to the ones a user has voted for (but not the ones they voted for)
+    pub async fn get_recommended_bots(&self, user_id: i64, limit: i64) -> Vec<models::IndexBot> {
+        let rows = sqlx::query!(
+            "SELECT bots.bot_id, bots.created_at, bots.flags, bots.description, 
+            bots.banner_card, bots.state, bots.votes, bots.guild_count FROM (
+                SELECT similar_bot_id, SUM(score) AS score FROM bot_similar 
+                WHERE bot_id IN (SELECT bot_id FROM bot_voters WHERE user_id = $1)
+                AND similar_bot_id NOT IN (SELECT bot_id FROM bot_voters WHERE user_id = $1)
+                GROUP BY similar_bot_id
+            ) recommended
+            INNER JOIN bots ON bots.bot_id = recommended.similar_bot_id
+            WHERE bots.state = $2 OR bots.state = $3
+            ORDER BY recommended.score DESC LIMIT $4",
+            user_id,
+            models::State::Approved as i32,
+            models::State::Certified as i32,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        let mut bots = Vec::new();
+
+        for row in rows {
+            bots.push(models::IndexBot {
+                guild_count: row.guild_count.unwrap_or(0),
+                description: row.description,
+                banner: row.banner_card.unwrap_or_else(|| {
+                    "https://api.fateslist.xyz/static/assets/prod/banner.webp".to_string()
+                }),
+                state: models::State::try_from(row.state).unwrap_or(models::State::Approved),
+                votes: row.votes.unwrap_or(0),
+                flags: row.flags,
+                user: self.get_user(row.bot_id).await,
+                review_stats: Some(self.get_review_stats(row.bot_id, models::TargetType::Bot).await),
+                search_meta: None,
+                created_at: row.created_at,
+            });
+        }
+
+        bots
+    }
+
     pub async fn bot_features(&self) -> Vec<models::Feature> {
         let mut features: Vec<models::Feature> = Vec::new();
         let rows = sqlx::query!("SELECT id, name, viewed_as, description FROM features")
@@ -324,12 +606,130 @@ impl Database {
         features
     }
 
-    pub async fn index_new_bots(&self) -> Vec<models::IndexBot> {
+    /// Bots ranked by votes (and to a lesser extent views) over the last ``hours`` hours, divided
+    /// by the log of their all time votes so large bots do not always win
+    pub async fn index_trending_bots(&self, hours: i32, opts: &models::IndexOptions) -> Vec<models::IndexBot> {
+        let mut bots: Vec<models::IndexBot> = Vec::new();
+        let rows = sqlx::query!(
+            "WITH recent_votes AS (
+                SELECT bot_id, COUNT(*) AS votes FROM bot_voters, unnest(timestamps) AS ts 
+                WHERE ts > NOW() - make_interval(hours => $1) GROUP BY bot_id
+            ), recent_views AS (
+                SELECT id AS bot_id, COUNT(*) AS views FROM ws_events 
+                WHERE type = 'bot' AND ts > NOW() - make_interval(hours => $1)
+                AND jsonb_path_exists(event, '$.*.m.e ? (@ == $e)', jsonb_build_object('e', $2::integer))
+                GROUP BY id
+            )
+            SELECT bots.bot_id, bots.flags, bots.created_at, bots.description, bots.banner_card, 
+            bots.state, bots.votes, bots.guild_count FROM bots
+            LEFT JOIN recent_votes ON recent_votes.bot_id = bots.bot_id
+            LEFT JOIN recent_views ON recent_views.bot_id = bots.bot_id
+            WHERE (bots.state = $3 OR bots.state = $4) 
+            AND (recent_votes.votes IS NOT NULL OR recent_views.views IS NOT NULL)
+            AND ($5::boolean OR NOT ($6 = ANY(bots.flags)))
+            AND ($7::bigint IS NULL OR bots.bot_id NOT IN (SELECT bot_id FROM bot_voters WHERE user_id = $7))
+            ORDER BY (COALESCE(recent_votes.votes, 0) + COALESCE(recent_views.views, 0) / 10.0) 
+            / LN(2 + COALESCE(bots.votes, 0)) DESC LIMIT $8",
+            hours,
+            models::EventName::BotView as i32,
+            models::State::Approved as i32,
+            models::State::Certified as i32,
+            opts.nsfw,
+            models::Flags::NSFW as i32,
+            opts.exclude_voted_by,
+            opts.limit,
+        )
+            .fetch_all(&self.pool)
+            .await
+            .unwrap();
+        for row in rows {
+            let bot = models::IndexBot {
+                guild_count: row.guild_count.unwrap_or(0),
+                description: row.description,
+                banner: row.banner_card.unwrap_or_else(|| {
+                    "https://api.fateslist.xyz/static/assets/prod/banner.webp".to_string()
+                }),
+                state: models::State::try_from(row.state).unwrap_or(models::State::Approved),
+                votes: row.votes.unwrap_or(0),
+                flags: row.flags,
+                user: self.get_user(row.bot_id).await,
+                review_stats: Some(self.get_review_stats(row.bot_id, models::TargetType::Bot).await),
+                search_meta: None,
+                created_at: row.created_at,
+            };
+            bots.push(bot);
+        }
+        bots
+    }
+
+    /// Same as ``index_trending_bots`` but for servers
+    pub async fn index_trending_servers(&self, hours: i32, opts: &models::IndexOptions) -> Vec<models::IndexBot> {
+        let mut servers: Vec<models::IndexBot> = Vec::new();
+        let rows = sqlx::query!(
+            "WITH recent_votes AS (
+                SELECT guild_id, COUNT(*) AS votes FROM server_voters, unnest(timestamps) AS ts 
+                WHERE ts > NOW() - make_interval(hours => $1) GROUP BY guild_id
+            ), recent_views AS (
+                SELECT id AS guild_id, COUNT(*) AS views FROM ws_events 
+                WHERE type = 'server' AND ts > NOW() - make_interval(hours => $1)
+                AND jsonb_path_exists(event, '$.*.m.e ? (@ == $e)', jsonb_build_object('e', $2::integer))
+                GROUP BY id
+            )
+            SELECT servers.guild_id, servers.flags, servers.created_at, servers.description, 
+            servers.banner_card, servers.state, servers.votes, servers.guild_count FROM servers
+            LEFT JOIN recent_votes ON recent_votes.guild_id = servers.guild_id
+            LEFT JOIN recent_views ON recent_views.guild_id = servers.guild_id
+            WHERE (servers.state = $3 OR servers.state = $4) 
+            AND (recent_votes.votes IS NOT NULL OR recent_views.views IS NOT NULL)
+            AND ($5::boolean OR NOT ($6 = ANY(servers.flags)))
+            AND ($7::bigint IS NULL OR servers.guild_id NOT IN (SELECT guild_id FROM server_voters WHERE user_id = $7))
+            ORDER BY (COALESCE(recent_votes.votes, 0) + COALESCE(recent_views.views, 0) / 10.0) 
+            / LN(2 + COALESCE(servers.votes, 0)) DESC LIMIT $8",
+            hours,
+            models::EventName::ServerView as i32,
+            models::State::Approved as i32,
+            models::State::Certified as i32,
+            opts.nsfw,
+            models::Flags::NSFW as i32,
+            opts.exclude_voted_by,
+            opts.limit,
+        )
+            .fetch_all(&self.pool)
+            .await
+            .unwrap();
+        for row in rows {
+            let server = models::IndexBot {
+                guild_count: row.guild_count.unwrap_or(0),
+                description: row.description,
+                banner: row.banner_card.unwrap_or_else(|| {
+                    "https://api.fateslist.xyz/static/assets/prod/banner.webp".to_string()
+                }),
+                flags: row.flags,
+                state: models::State::try_from(row.state).unwrap_or(models::State::Approved),
+                votes: row.votes.unwrap_or(0),
+                user: self.get_server_user(row.guild_id).await,
+                review_stats: Some(self.get_review_stats(row.guild_id, models::TargetType::Server).await),
+                search_meta: None,
+                created_at: row.created_at,
+            };
+            servers.push(server);
+        }
+        servers
+    }
+
+    pub async fn index_new_bots(&self, opts: &models::IndexOptions) -> Vec<models::IndexBot> {
         let mut bots: Vec<models::IndexBot> = Vec::new();
         let rows = sqlx::query!(
             "SELECT bot_id, flags, created_at, description, banner_card, state, votes, guild_count 
-            FROM bots WHERE state = $1 ORDER BY created_at DESC LIMIT 12",
-            models::State::Approved as i32
+            FROM bots WHERE state = $1
+            AND ($2::boolean OR NOT ($3 = ANY(flags)))
+            AND ($4::bigint IS NULL OR bot_id NOT IN (SELECT bot_id FROM bot_voters WHERE user_id = $4))
+            ORDER BY created_at DESC LIMIT $5",
+            models::State::Approved as i32,
+            opts.nsfw,
+            models::Flags::NSFW as i32,
+            opts.exclude_voted_by,
+            opts.limit,
         )
             .fetch_all(&self.pool)
             .await
@@ -345,6 +745,8 @@ impl Database {
                 votes: row.votes.unwrap_or(0),
                 flags: row.flags,
                 user: self.get_user(row.bot_id).await,
+                review_stats: Some(self.get_review_stats(row.bot_id, models::TargetType::Bot).await),
+                search_meta: None,
                 created_at: row.created_at,
             };
             bots.push(bot);
@@ -367,12 +769,19 @@ impl Database {
         }
     }
 
-    pub async fn index_servers(&self, state: models::State) -> Vec<models::IndexBot> {
+    pub async fn index_servers(&self, state: models::State, opts: &models::IndexOptions) -> Vec<models::IndexBot> {
         let mut servers: Vec<models::IndexBot> = Vec::new();
         let rows = sqlx::query!(
             "SELECT guild_id, flags, description, created_at, banner_card, state, votes, guild_count 
-            FROM servers WHERE state = $1 ORDER BY votes DESC LIMIT 12",
-            state as i32
+            FROM servers WHERE state = $1
+            AND ($2::boolean OR NOT ($3 = ANY(flags)))
+            AND ($4::bigint IS NULL OR guild_id NOT IN (SELECT guild_id FROM server_voters WHERE user_id = $4))
+            ORDER BY votes DESC LIMIT $5",
+            state as i32,
+            opts.nsfw,
+            models::Flags::NSFW as i32,
+            opts.exclude_voted_by,
+            opts.limit,
         )
             .fetch_all(&self.pool)
             .await
@@ -388,6 +797,8 @@ impl Database {
                 state: models::State::try_from(row.state).unwrap_or(state),
                 votes: row.votes.unwrap_or(0),
                 user: self.get_server_user(row.guild_id).await,
+                review_stats: Some(self.get_review_stats(row.guild_id, models::TargetType::Server).await),
+                search_meta: None,
                 created_at: row.created_at,
             };
             servers.push(server);
@@ -395,12 +806,19 @@ impl Database {
         servers
     }
 
-    pub async fn index_new_servers(&self) -> Vec<models::IndexBot> {
+    pub async fn index_new_servers(&self, opts: &models::IndexOptions) -> Vec<models::IndexBot> {
         let mut servers: Vec<models::IndexBot> = Vec::new();
         let rows = sqlx::query!(
             "SELECT guild_id, flags, description, created_at, banner_card, state, votes, guild_count 
-            FROM servers WHERE state = $1 ORDER BY created_at DESC LIMIT 12",
-            models::State::Approved as i32
+            FROM servers WHERE state = $1
+            AND ($2::boolean OR NOT ($3 = ANY(flags)))
+            AND ($4::bigint IS NULL OR guild_id NOT IN (SELECT guild_id FROM server_voters WHERE user_id = $4))
+            ORDER BY created_at DESC LIMIT $5",
+            models::State::Approved as i32,
+            opts.nsfw,
+            models::Flags::NSFW as i32,
+            opts.exclude_voted_by,
+            opts.limit,
         )
             .fetch_all(&self.pool)
             .await
@@ -416,6 +834,8 @@ impl Database {
                 votes: row.votes.unwrap_or(0),
                 flags: row.flags,
                 user: self.get_server_user(row.guild_id).await,
+                review_stats: Some(self.get_review_stats(row.guild_id, models::TargetType::Server).await),
+                search_meta: None,
                 created_at: row.created_at,
             };
             servers.push(server);
@@ -656,12 +1076,17 @@ impl Database {
         owners
     }
 
-    pub async fn get_bot_events(&self, bot_id: i64) -> Vec<models::BotEvent> {
+    /// Gets the events of a bot. Unless ``include_inactive`` is set, only events that have 
+    /// started and not yet ended are returned
+    pub async fn get_bot_events(&self, bot_id: i64, include_inactive: bool) -> Vec<models::BotEvent> {
         let mut events = Vec::new();
 
         let events_row = sqlx::query!(
-            "SELECT id, event_type, ts, css, reason FROM bot_events WHERE bot_id = $1",
-            bot_id
+            "SELECT id, event_type, ts, css, reason, starts_at, ends_at, pause_invites FROM bot_events 
+            WHERE bot_id = $1 AND ($2 OR (starts_at <= NOW() AND (ends_at IS NULL OR ends_at > NOW())))
+            ORDER BY starts_at DESC",
+            bot_id,
+            include_inactive,
         )
         .fetch_all(&self.pool)
         .await;
@@ -678,8 +1103,15 @@ impl Database {
                 event_type: models::BotEventType::try_from(row.event_type)
                     .unwrap_or(models::BotEventType::Promotion),
                 ts: row.ts,
-                css: row.css,
+                css: converters::sanitize_description(
+                    models::LongDescriptionType::Html,
+                    &("<style>".to_string() + &row.css + "</style>"),
+                ),
+                css_raw: row.css,
                 reason: row.reason,
+                starts_at: row.starts_at,
+                ends_at: row.ends_at,
+                pause_invites: row.pause_invites,
             };
             events.push(event);
         }
@@ -687,70 +1119,495 @@ impl Database {
         events
     }
 
-    pub async fn get_bot_commands(&self, bot_id: i64) -> Vec<models::BotCommand> {
-        // Commands
-        let mut commands = Vec::new();
+    pub async fn is_bot_owner(&self, bot_id: i64, user_id: i64) -> bool {
+        sqlx::query!(
+            "SELECT bot_id FROM bot_owner WHERE bot_id = $1 AND owner = $2",
+            bot_id,
+            user_id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_or(false, |row| row.is_some())
+    }
 
-        let commands_rows = sqlx::query!(
-            "SELECT id, cmd_type, description, args, examples, 
-            premium_only, notes, doc_link, groups, name, 
-            vote_locked, nsfw FROM bot_commands WHERE bot_id = $1",
-            bot_id
+    /// Bots that are checked by the uptime checker
+    pub async fn get_uptime_bot_ids(&self) -> Vec<i64> {
+        sqlx::query!(
+            "SELECT bot_id FROM bots WHERE state = $1 OR state = $2",
+            models::State::Approved as i32,
+            models::State::Certified as i32,
         )
         .fetch_all(&self.pool)
         .await
-        .unwrap();
+        .map(|rows| rows.into_iter().map(|row| row.bot_id).collect())
+        .unwrap_or_default()
+    }
 
-        for command in commands_rows {
-            commands.push(models::BotCommand {
-                id: Some(command.id.to_string()),
-                nsfw: command.nsfw.unwrap_or(false),
-                cmd_type: models::CommandType::try_from(command.cmd_type)
-                    .unwrap_or(models::CommandType::SlashCommandGlobal),
-                description: command.description.to_string(),
-                args: command.args,
-                examples: command.examples,
-                premium_only: command.premium_only,
-                notes: command.notes,
-                doc_link: command.doc_link,
-                name: command.name,
-                vote_locked: command.vote_locked,
-                groups: command.groups,
-            });
-        }
+    /// Stores uptime checks and opens or closes incidents. Checks of bots in maintenance are 
+    /// stored but are not counted against the bot and do not open incidents
+    pub async fn record_uptime_checks(&self, checks: &[(i64, bool)]) -> Result<(), sqlx::Error> {
+        let bot_ids: Vec<i64> = checks.iter().map(|(bot_id, _)| *bot_id).collect();
 
-        commands
-    }
+        let mut tx = self.pool.begin().await?;
 
+        let maintenance: Vec<i64> = sqlx::query!(
+            "SELECT DISTINCT bot_id FROM bot_events WHERE bot_id = ANY($1) AND event_type = $2 
+            AND starts_at <= NOW() AND (ends_at IS NULL OR ends_at > NOW())",
+            &bot_ids,
+            models::BotEventType::Maintenance as i32,
+        )
+        .fetch_all(&mut tx)
+        .await?
+        .into_iter()
+        .map(|row| row.bot_id)
+        .collect();
 
-    pub async fn get_bot(&self, bot_id: i64) -> Option<models::Bot> {
-        let row = sqlx::query!(
-            "SELECT bot_id, created_at, last_stats_post, description, 
-            css, flags, banner_card, banner_page, guild_count, shard_count, 
-            shards, prefix, invite, invite_amount, features, bot_library 
-            AS library, state, user_count, votes, total_votes,
-            client_id, uptime_checks_total, uptime_checks_failed, 
-            page_style, long_description_type, last_updated_at,
-            long_description, webhook_type, extra_links FROM bots WHERE bot_id = $1 OR 
-            client_id = $1",
-            bot_id
+        let online: Vec<bool> = checks.iter().map(|(_, online)| *online).collect();
+        let in_maintenance: Vec<bool> = bot_ids.iter().map(|bot_id| maintenance.contains(bot_id)).collect();
+
+        sqlx::query!(
+            "INSERT INTO bot_uptime_checks (bot_id, online, in_maintenance) 
+            SELECT * FROM unnest($1::bigint[], $2::boolean[], $3::boolean[])",
+            &bot_ids,
+            &online,
+            &in_maintenance,
         )
-        .fetch_one(&self.pool)
-        .await;
+        .execute(&mut tx)
+        .await?;
 
-        match row {
-            Ok(data) => {
-                // Handle client id
-                let mut client_id: String = data.bot_id.to_string();
-                match data.client_id {
-                    Some(c_id) => {
-                        client_id = c_id.to_string();
-                    }
-                    None => {}
-                };
+        let counted: Vec<(i64, bool)> = checks
+            .iter()
+            .filter(|(bot_id, _)| !maintenance.contains(bot_id))
+            .copied()
+            .collect();
 
-                // Sanitize long description
-                let long_description_type =
+        let up: Vec<i64> = counted.iter().filter(|(_, online)| *online).map(|(bot_id, _)| *bot_id).collect();
+        let down: Vec<i64> = counted.iter().filter(|(_, online)| !*online).map(|(bot_id, _)| *bot_id).collect();
+
+        sqlx::query!(
+            "UPDATE bots SET uptime_checks_total = COALESCE(uptime_checks_total, 0) + 1 
+            WHERE bot_id = ANY($1)",
+            &up,
+        )
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE bots SET uptime_checks_total = COALESCE(uptime_checks_total, 0) + 1, 
+            uptime_checks_failed = COALESCE(uptime_checks_failed, 0) + 1 
+            WHERE bot_id = ANY($1)",
+            &down,
+        )
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE bot_uptime_incidents SET ended_at = NOW() WHERE ended_at IS NULL AND bot_id = ANY($1)",
+            &up,
+        )
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO bot_uptime_incidents (bot_id) SELECT unnest($1::bigint[]) 
+            ON CONFLICT (bot_id) WHERE ended_at IS NULL DO NOTHING",
+            &down,
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Uptime checks are only needed for the uptime ranges, the longest of which is 30 days
+    pub async fn prune_uptime_checks(&self) {
+        let res = sqlx::query!("DELETE FROM bot_uptime_checks WHERE checked_at < NOW() - INTERVAL '31 days'")
+            .execute(&self.pool)
+            .await;
+
+        if let Err(err) = res {
+            error!("Error pruning uptime checks: {}", err);
+        }
+    }
+
+    /// Notifies the owners of bots that have been offline for ``minutes`` minutes, once per outage. 
+    /// Bots in maintenance are skipped until their maintenance ends
+    pub async fn notify_outages(&self, minutes: i32) {
+        let incidents = sqlx::query!(
+            "UPDATE bot_uptime_incidents SET owners_notified = true 
+            WHERE ended_at IS NULL AND NOT owners_notified 
+            AND started_at <= NOW() - make_interval(mins => $1) 
+            AND NOT EXISTS (
+                SELECT 1 FROM bot_events WHERE bot_events.bot_id = bot_uptime_incidents.bot_id 
+                AND event_type = $2 AND starts_at <= NOW() AND (ends_at IS NULL OR ends_at > NOW())
+            ) 
+            RETURNING bot_id",
+            minutes,
+            models::BotEventType::Maintenance as i32,
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        let incidents = match incidents {
+            Ok(incidents) => incidents,
+            Err(err) => {
+                error!("Error getting outages: {}", err);
+                return;
+            }
+        };
+
+        for incident in incidents {
+            let bot = self.get_user(incident.bot_id).await;
+
+            let title = format!(
+                "{} has been offline for over {} minutes",
+                bot.username,
+                minutes
+            );
+
+            for owner in self.get_bot_owners(incident.bot_id).await {
+                if let Ok(owner_id) = owner.user.id.parse::<i64>() {
+                    self.notify_user(owner_id, &title).await;
+                }
+            }
+        }
+    }
+
+    pub async fn get_bot_uptime(&self, bot_id: i64, range: models::UptimeRange) -> models::BotUptime {
+        let days = range.days();
+
+        let counts = sqlx::query!(
+            "SELECT COUNT(*) FILTER (WHERE NOT in_maintenance) AS \"checks!\", 
+            COUNT(*) FILTER (WHERE NOT in_maintenance AND NOT online) AS \"failed!\" 
+            FROM bot_uptime_checks WHERE bot_id = $1 AND checked_at > NOW() - make_interval(days => $2)",
+            bot_id,
+            days,
+        )
+        .fetch_one(&self.pool)
+        .await;
+
+        let (checks, failed) = counts.map_or((0, 0), |row| (row.checks, row.failed));
+
+        let incidents = sqlx::query!(
+            "SELECT started_at, ended_at FROM bot_uptime_incidents WHERE bot_id = $1 
+            AND (ended_at IS NULL OR ended_at > NOW() - make_interval(days => $2)) 
+            ORDER BY started_at DESC",
+            bot_id,
+            days,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        let now = chrono::Utc::now();
+
+        models::BotUptime {
+            range,
+            checks,
+            failed,
+            percentage: if checks > 0 {
+                Some((checks - failed) as f64 * 100.0 / checks as f64)
+            } else {
+                None
+            },
+            incidents: incidents
+                .into_iter()
+                .map(|incident| models::UptimeIncident {
+                    started_at: incident.started_at,
+                    ended_at: incident.ended_at,
+                    duration: (incident.ended_at.unwrap_or(now) - incident.started_at).num_seconds(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether the bot has an active maintenance event that pauses invites
+    pub async fn invites_paused(&self, bot_id: i64) -> bool {
+        sqlx::query!(
+            "SELECT id FROM bot_events WHERE bot_id = $1 AND event_type = $2 AND pause_invites 
+            AND starts_at <= NOW() AND (ends_at IS NULL OR ends_at > NOW()) LIMIT 1",
+            bot_id,
+            models::BotEventType::Maintenance as i32,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_or(false, |row| row.is_some())
+    }
+
+    /// Counts the events of a bot that have not ended yet
+    pub async fn count_upcoming_bot_events(&self, bot_id: i64) -> i64 {
+        sqlx::query!(
+            "SELECT COUNT(*) AS \"count!\" FROM bot_events 
+            WHERE bot_id = $1 AND (ends_at IS NULL OR ends_at > NOW())",
+            bot_id,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_or(0, |row| row.count)
+    }
+
+    pub async fn add_bot_event(&self, bot_id: i64, event: &models::BotEventData) -> Result<uuid::Uuid, sqlx::Error> {
+        let row = sqlx::query!(
+            "INSERT INTO bot_events (bot_id, event_type, reason, css, starts_at, ends_at, pause_invites) 
+            VALUES ($1, $2, $3, $4, COALESCE($5, NOW()), $6, $7) RETURNING id",
+            bot_id,
+            event.event_type as i32,
+            event.reason,
+            event.css,
+            event.starts_at,
+            event.ends_at,
+            event.pause_invites && event.event_type == models::BotEventType::Maintenance,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        self.bot_cache.invalidate(&bot_id).await;
+
+        Ok(row.id)
+    }
+
+    /// When an event of the bot starts, ``None`` if the bot has no such event
+    pub async fn get_bot_event_start(
+        &self,
+        bot_id: i64,
+        id: uuid::Uuid,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, sqlx::Error> {
+        let row = sqlx::query!(
+            "SELECT starts_at FROM bot_events WHERE bot_id = $1 AND id = $2",
+            bot_id,
+            id,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| row.starts_at))
+    }
+
+    /// Edits an event, returns false if the bot has no such event. An active event is ended 
+    /// and then announced again once it is active
+    pub async fn edit_bot_event(
+        &self,
+        bot_id: i64,
+        id: uuid::Uuid,
+        event: &models::BotEventData,
+    ) -> Result<bool, sqlx::Error> {
+        let row = sqlx::query!(
+            "WITH old AS (
+                SELECT id, event_type, start_announced AND NOT end_announced AS active FROM bot_events 
+                WHERE bot_id = $6 AND id = $7 FOR UPDATE
+            )
+            UPDATE bot_events SET event_type = $1, reason = $2, css = $3, 
+            starts_at = COALESCE($4, starts_at), ends_at = $5, pause_invites = $8,
+            start_announced = false, end_announced = false 
+            FROM old WHERE bot_events.id = old.id 
+            RETURNING old.active AS \"was_active!\", old.event_type AS \"old_event_type!\"",
+            event.event_type as i32,
+            event.reason,
+            event.css,
+            event.starts_at,
+            event.ends_at,
+            bot_id,
+            id,
+            event.pause_invites && event.event_type == models::BotEventType::Maintenance,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => {
+                if row.was_active {
+                    let old_event_type = models::BotEventType::try_from(row.old_event_type)
+                        .unwrap_or(models::BotEventType::Promotion);
+                    self.send_bot_event_end(bot_id, id, old_event_type).await;
+                }
+                self.bot_cache.invalidate(&bot_id).await;
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    /// Deletes an event, returns false if the bot has no such event. Clients are told if the 
+    /// event was active
+    pub async fn delete_bot_event(&self, bot_id: i64, id: uuid::Uuid) -> Result<bool, sqlx::Error> {
+        let row = sqlx::query!(
+            "DELETE FROM bot_events WHERE bot_id = $1 AND id = $2 
+            RETURNING event_type, start_announced AND NOT end_announced AS \"active!\"",
+            bot_id,
+            id,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => {
+                if row.active {
+                    let event_type = models::BotEventType::try_from(row.event_type)
+                        .unwrap_or(models::BotEventType::Promotion);
+                    self.send_bot_event_end(bot_id, id, event_type).await;
+                }
+                self.bot_cache.invalidate(&bot_id).await;
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    async fn send_bot_event_end(&self, bot_id: i64, id: uuid::Uuid, event_type: models::BotEventType) {
+        self.ws_event(models::Event {
+            m: models::EventMeta {
+                e: models::EventName::BotEventEnd,
+                eid: uuid::Uuid::new_v4().to_hyphenated().to_string(),
+            },
+            ctx: models::EventContext {
+                target: bot_id.to_string(),
+                target_type: models::TargetType::Bot,
+                user: None,
+                ts: chrono::Utc::now().timestamp(),
+            },
+            props: models::BotEventEndProp {
+                id: id.to_string(),
+                event_type,
+            },
+        }).await;
+    }
+
+    /// Sends ``BotEventStart`` for events that became active and ``BotEventEnd`` for events 
+    /// that ended since the last call
+    pub async fn announce_bot_events(&self) {
+        let started = sqlx::query!(
+            "UPDATE bot_events SET start_announced = true 
+            WHERE NOT start_announced AND starts_at <= NOW() AND (ends_at IS NULL OR ends_at > NOW()) 
+            RETURNING id, bot_id, event_type, ts, css, reason, starts_at, ends_at, pause_invites"
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        match started {
+            Ok(rows) => {
+                for row in rows {
+                    // Bots show active events (and whether they are in maintenance)
+                    self.bot_cache.invalidate(&row.bot_id).await;
+
+                    self.ws_event(models::Event {
+                        m: models::EventMeta {
+                            e: models::EventName::BotEventStart,
+                            eid: uuid::Uuid::new_v4().to_hyphenated().to_string(),
+                        },
+                        ctx: models::EventContext {
+                            target: row.bot_id.to_string(),
+                            target_type: models::TargetType::Bot,
+                            user: None,
+                            ts: chrono::Utc::now().timestamp(),
+                        },
+                        props: models::BotEvent {
+                            id: row.id.to_string(),
+                            event_type: models::BotEventType::try_from(row.event_type)
+                                .unwrap_or(models::BotEventType::Promotion),
+                            ts: row.ts,
+                            css: converters::sanitize_description(
+                                models::LongDescriptionType::Html,
+                                &("<style>".to_string() + &row.css + "</style>"),
+                            ),
+                            css_raw: row.css,
+                            reason: row.reason,
+                            starts_at: row.starts_at,
+                            ends_at: row.ends_at,
+                            pause_invites: row.pause_invites,
+                        },
+                    }).await;
+                }
+            },
+            Err(err) => error!("Error announcing started bot events: {}", err),
+        }
+
+        let ended = sqlx::query!(
+            "UPDATE bot_events SET start_announced = true, end_announced = true 
+            WHERE NOT end_announced AND ends_at <= NOW() 
+            RETURNING id, bot_id, event_type"
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        match ended {
+            Ok(rows) => {
+                for row in rows {
+                    self.bot_cache.invalidate(&row.bot_id).await;
+                    let event_type = models::BotEventType::try_from(row.event_type)
+                        .unwrap_or(models::BotEventType::Promotion);
+                    self.send_bot_event_end(row.bot_id, row.id, event_type).await;
+                }
+            },
+            Err(err) => error!("Error announcing ended bot events: {}", err),
+        }
+    }
+
+    pub async fn get_bot_commands(&self, bot_id: i64) -> Vec<models::BotCommand> {
+        // Commands
+        let mut commands = Vec::new();
+
+        let commands_rows = sqlx::query!(
+            "SELECT id, cmd_type, description, args, examples, 
+            premium_only, notes, doc_link, groups, name, 
+            vote_locked, nsfw FROM bot_commands WHERE bot_id = $1",
+            bot_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap();
+
+        for command in commands_rows {
+            commands.push(models::BotCommand {
+                id: Some(command.id.to_string()),
+                nsfw: command.nsfw.unwrap_or(false),
+                cmd_type: models::CommandType::try_from(command.cmd_type)
+                    .unwrap_or(models::CommandType::SlashCommandGlobal),
+                description: command.description.to_string(),
+                args: serde_json::from_value(command.args).unwrap_or_default(),
+                examples: command.examples,
+                premium_only: command.premium_only,
+                notes: command.notes,
+                doc_link: command.doc_link,
+                name: command.name,
+                vote_locked: command.vote_locked,
+                groups: command.groups,
+            });
+        }
+
+        commands
+    }
+
+
+    pub async fn get_bot(&self, bot_id: i64) -> Option<models::Bot> {
+        let row = sqlx::query!(
+            "SELECT bot_id, created_at, last_stats_post, description, 
+            css, flags, banner_card, banner_page, guild_count, shard_count, 
+            shards, prefix, invite, invite_amount, features, bot_library 
+            AS library, state, user_count, votes, total_votes,
+            client_id, uptime_checks_total, uptime_checks_failed, 
+            page_style, long_description_type, last_updated_at,
+            long_description, webhook_type, extra_links FROM bots WHERE bot_id = $1 OR 
+            client_id = $1",
+            bot_id
+        )
+        .fetch_one(&self.pool)
+        .await;
+
+        match row {
+            Ok(data) => {
+                // Handle client id
+                let mut client_id: String = data.bot_id.to_string();
+                match data.client_id {
+                    Some(c_id) => {
+                        client_id = c_id.to_string();
+                    }
+                    None => {}
+                };
+
+                // Sanitize long description
+                let long_description_type =
                     models::LongDescriptionType::try_from(data.long_description_type)
                         .unwrap_or(models::LongDescriptionType::MarkdownServerSide);
 
@@ -805,12 +1662,22 @@ impl Database {
                     extra_links.insert(key.clone(), value.as_str().unwrap_or_default().to_string());
                 }
 
+                // Only active events are shown
+                let events = self.get_bot_events(bot_id, false).await;
+
+                let maintenance = events
+                    .iter()
+                    .find(|event| event.event_type == models::BotEventType::Maintenance)
+                    .cloned();
+
                 // Make the struct
                 let bot = models::Bot {
                     extra_links,
                     created_at: data.created_at,
                     vpm: Some(self.get_votes_per_month(bot_id).await),
-                    events: self.get_bot_events(bot_id).await,
+                    events,
+                    in_maintenance: maintenance.is_some(),
+                    maintenance,
                     last_stats_post: data.last_stats_post,
                     last_updated_at: data.last_updated_at,
                     description: data.description,
@@ -835,6 +1702,7 @@ impl Database {
                     client_id,
                     tags: self.get_bot_tags(bot_id).await,
                     commands: self.get_bot_commands(bot_id).await,
+                    review_stats: self.get_review_stats(bot_id, models::TargetType::Bot).await,
                     long_description_type,
                     long_description: long_description_parsed,
                     long_description_raw: data.long_description,
@@ -965,51 +1833,256 @@ impl Database {
         }
     }
 
-    pub async fn resolve_pack_bots(&self, bots: Vec<i64>) -> Vec<models::ResolvedPackBot> {
+    /// Resolves the bots of a pack (with their notes). Bots that do not exist, are banned/denied
+    /// or are NSFW are rejected
+    pub async fn resolve_pack_bots(&self, bots: Vec<(i64, Option<String>)>) -> Result<Vec<models::ResolvedPackBot>, models::PackCheckError> {
         let mut resolved_bots = Vec::new();
-        for bot in bots {
-            let description = sqlx::query!("SELECT description FROM bots WHERE bot_id = $1", bot)
-                .fetch_one(&self.pool)
-                .await;
+        for (bot, note) in bots {
+            let row = sqlx::query!("SELECT description, state, flags FROM bots WHERE bot_id = $1", bot)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(models::PackCheckError::SQLError)?
+                .ok_or(models::PackCheckError::BotNotFound(bot))?;
 
-            if let Ok(desc) = description {
-                resolved_bots.push(models::ResolvedPackBot {
-                    user: self.get_user(bot).await,
-                    description: desc.description,
-                });
-            } else {
-                // The bot does not exist, maybe deleted? TODO: Delete
+            let state = models::State::try_from(row.state).unwrap_or(models::State::Banned);
+
+            if state == models::State::Banned 
+            || state == models::State::Denied 
+            || row.flags.contains(&(models::Flags::NSFW as i32)) {
+                return Err(models::PackCheckError::BotNotAllowed(bot));
             }
+
+            resolved_bots.push(models::ResolvedPackBot {
+                user: self.get_user(bot).await,
+                description: row.description,
+                note,
+            });
         }
-        resolved_bots
+        Ok(resolved_bots)
     }
 
-    pub async fn search(&self, search: models::SearchQuery) -> models::Search {
-        // Get bots row
+    /// Gets a pack by id regardless of its visibility
+    pub async fn get_pack(&self, pack_id: &str) -> Option<models::BotPack> {
+        let id = uuid::Uuid::parse_str(pack_id).ok()?;
+
+        let pack = sqlx::query!(
+            "SELECT id, icon, banner, created_at, owner, description, name, visibility,
+            (SELECT COUNT(*) FROM bot_pack_followers WHERE bot_pack_followers.pack_id = bot_packs.id) AS \"followers!\"
+            FROM bot_packs WHERE id = $1",
+            id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .ok()?;
+
+        let bots = sqlx::query!(
+            "SELECT bot_pack_bots.bot_id, bot_pack_bots.note, bots.description FROM bot_pack_bots 
+            INNER JOIN bots ON bots.bot_id = bot_pack_bots.bot_id
+            WHERE bot_pack_bots.pack_id = $1 ORDER BY bot_pack_bots.position",
+            id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        let mut resolved_bots = Vec::new();
+
+        for bot in bots {
+            resolved_bots.push(models::ResolvedPackBot {
+                user: self.get_user(bot.bot_id).await,
+                description: bot.description,
+                note: bot.note,
+            });
+        }
+
+        let mut editors = Vec::new();
+
+        for editor in self.get_pack_editors(pack_id).await {
+            editors.push(self.get_user(editor).await);
+        }
+
+        Some(models::BotPack {
+            id: pack.id.to_string(),
+            name: pack.name,
+            description: pack.description,
+            icon: pack.icon.unwrap_or_default(),
+            banner: pack.banner.unwrap_or_else(|| {
+                "https://api.fateslist.xyz/static/assets/prod/banner.webp".to_string()
+            }),
+            owner: self.get_user(pack.owner).await,
+            editors,
+            visibility: models::PackVisibility::try_from(pack.visibility).unwrap_or_default(),
+            followers: pack.followers,
+            created_at: pack.created_at.unwrap_or_else(|| {
+                chrono::DateTime::<chrono::Utc>::from_utc(
+                    chrono::NaiveDateTime::from_timestamp(0, 0),
+                    chrono::Utc,
+                )
+            }),
+            resolved_bots,
+        })
+    }
+
+    /// Searches bots, servers, profiles and packs. Errors are logged and give an empty result
+    pub async fn search(&self, search: models::SearchQuery, per_page: i64, offset: i64) -> models::Search {
+
+        // Every word is prefix matched, typos are handled using trigram similarity on names
+        let tsquery = converters::to_prefix_tsquery(&search.q);
+        let q = search.q.trim().to_string();
+
+        // Facets
+        let tags = split_list(&search.tags);
+        let features = split_list(&search.features);
+        let tag_mode_or = search.tag_mode.unwrap_or_default() == models::TagMode::Or;
+        let certified_only = search.certified_only.unwrap_or(false);
+
+        let (state_a, state_b) = if certified_only {
+            (models::State::Certified, models::State::Certified)
+        } else {
+            (models::State::Approved, models::State::Certified)
+        };
+
+        /* Matching bots are collected once, the current page and the facet counts are then taken 
+        from them. Matching only uses the indexed search vector (owners are matched on their own), 
+        the owner vector is only used for ranking
+        */
+        let bot_matches = sqlx::query!(
+            "WITH matches AS MATERIALIZED (
+                SELECT bots.bot_id, bots.votes, bots.guild_count, bots.features, bots.bot_library, 
+                bots.state, bots.flags, bots.page_style,
+                ts_rank(bots.search_vector || owners.search_vector, query) 
+                + similarity(bots.username_cached, $2) AS rank
+                FROM bots
+                CROSS JOIN to_tsquery('simple', $1) query
+                CROSS JOIN LATERAL (
+                    SELECT setweight(to_tsvector('simple', 
+                    COALESCE(string_agg(COALESCE(users.username, '') || ' ' || bot_owner.owner::text, ' '), '')), 'D') 
+                    AS search_vector FROM bot_owner LEFT JOIN users ON users.user_id = bot_owner.owner 
+                    WHERE bot_owner.bot_id = bots.bot_id
+                ) owners
+                WHERE ($2 = '' OR bots.search_vector @@ query OR bots.username_cached % $2 OR bots.bot_id IN (
+                    SELECT bot_owner.bot_id FROM bot_owner INNER JOIN users ON users.user_id = bot_owner.owner 
+                    WHERE to_tsvector('simple', COALESCE(users.username, '')) @@ query OR bot_owner.owner::text = $2
+                )) 
+                AND (bots.state = $3 OR bots.state = $4) 
+                AND (bots.guild_count > $5)
+                AND (($6 = -1::bigint) OR (bots.guild_count < $6))
+                AND ($7::text[] IS NULL OR (
+                    SELECT COUNT(DISTINCT bot_tags.tag) FROM bot_tags 
+                    WHERE bot_tags.bot_id = bots.bot_id AND bot_tags.tag = ANY($7)
+                ) >= (CASE WHEN $8 THEN 1 ELSE cardinality($7) END))
+                AND ($9::text[] IS NULL OR bots.features @> $9)
+                AND ($10::text IS NULL OR LOWER(bots.bot_library) = LOWER($10))
+                AND ($11::integer IS NULL OR bots.page_style = $11)
+                AND ($12::boolean IS NULL OR ($13 = ANY(bots.flags)) = $12)
+                AND ($14::timestamptz IS NULL OR bots.created_at > $14)
+            ), page AS (
+                SELECT bot_id, rank, row_number() OVER (
+                    ORDER BY rank DESC, votes DESC, guild_count DESC, bot_id
+                ) AS pos FROM matches ORDER BY pos LIMIT $15 OFFSET $16
+            )
+            SELECT (SELECT COUNT(*) FROM matches) AS \"total!\",
+            ARRAY(SELECT bot_id FROM page ORDER BY pos) AS \"page_ids!\",
+            ARRAY(SELECT rank FROM page ORDER BY pos) AS \"page_ranks!\",
+            (
+                SELECT json_object_agg(tag, count ORDER BY count DESC) FROM (
+                    SELECT bot_tags.tag, COUNT(*) AS count FROM bot_tags 
+                    INNER JOIN matches ON matches.bot_id = bot_tags.bot_id GROUP BY 1
+                ) tags
+            ) AS tag_facets,
+            (
+                SELECT json_object_agg(feature, count ORDER BY count DESC) FROM (
+                    SELECT unnest(features) AS feature, COUNT(*) AS count FROM matches GROUP BY 1
+                ) features
+            ) AS feature_facets,
+            (
+                SELECT json_object_agg(library, count ORDER BY count DESC) FROM (
+                    SELECT COALESCE(bot_library, '') AS library, COUNT(*) AS count FROM matches GROUP BY 1
+                ) libraries
+            ) AS library_facets,
+            (SELECT COUNT(*) FROM matches WHERE state = $17) AS \"certified!\",
+            (SELECT COUNT(*) FROM matches WHERE $13 = ANY(flags)) AS \"nsfw!\",
+            (SELECT COUNT(*) FROM matches WHERE page_style = $18) AS \"tabs!\",
+            (SELECT COUNT(*) FROM matches WHERE page_style = $19) AS \"single_scroll!\"",
+            tsquery,
+            q,
+            state_a as i32,
+            state_b as i32,
+            search.gc_from,
+            search.gc_to,
+            tags.as_deref(),
+            tag_mode_or,
+            features.as_deref(),
+            search.library,
+            search.page_style.map(|style| style as i32),
+            search.nsfw,
+            models::Flags::NSFW as i32,
+            search.created_after,
+            per_page,
+            offset,
+            models::State::Certified as i32,
+            models::PageStyle::Tabs as i32,
+            models::PageStyle::SingleScroll as i32,
+        )
+        .fetch_one(&self.pool)
+        .await;
+
+        let bot_matches = match bot_matches {
+            Ok(bot_matches) => bot_matches,
+            Err(err) => {
+                error!("Error searching bots: {}", err);
+                return models::Search::default();
+            }
+        };
+
+        let mut facets = models::SearchFacets {
+            total: bot_matches.total,
+            tags: facet_counts(bot_matches.tag_facets),
+            features: facet_counts(bot_matches.feature_facets),
+            libraries: facet_counts(bot_matches.library_facets),
+            page_styles: IndexMap::new(),
+            certified: bot_matches.certified,
+            nsfw: bot_matches.nsfw,
+        };
+
+        facets.page_styles.insert(format!("{:?}", models::PageStyle::Tabs), bot_matches.tabs);
+        facets.page_styles.insert(format!("{:?}", models::PageStyle::SingleScroll), bot_matches.single_scroll);
+
         let bots_row = sqlx::query!(
-            "SELECT DISTINCT bots.bot_id, bots.created_at,
+            "SELECT bots.bot_id, bots.created_at,
             bots.description, bots.banner_card AS banner, bots.state, 
-            bots.votes, bots.flags, bots.guild_count FROM bots 
-            INNER JOIN bot_owner ON bots.bot_id = bot_owner.bot_id 
-            WHERE (bots.description ilike $1 
-            OR bots.long_description ilike $1 
-            OR bots.username_cached ilike $1 
-            OR bot_owner.owner::text ilike $1) 
-            AND (bots.state = $2 OR bots.state = $3) 
-            AND (bots.guild_count > $4)
-            AND (($5 = -1::bigint) OR (bots.guild_count < $5))
-            ORDER BY bots.votes DESC, bots.guild_count DESC LIMIT 6",
-            "%".to_string() + &search.q + "%",
-            models::State::Approved as i32,
-            models::State::Certified as i32,
-            search.gc_from,
-            search.gc_to
+            bots.votes, bots.flags, bots.guild_count,
+            ts_headline('simple', bots.description, query, 
+            'StartSel=[[[, StopSel=]]], MaxWords=35, MinWords=15') AS \"snippet!\"
+            FROM bots CROSS JOIN to_tsquery('simple', $1) query
+            WHERE bots.bot_id = ANY($2)",
+            tsquery,
+            &bot_matches.page_ids,
         )
         .fetch_all(&self.pool)
-        .await
-        .unwrap();
-        let mut bots = Vec::new();
+        .await;
+
+        let bots_row = match bots_row {
+            Ok(bots_row) => bots_row,
+            Err(err) => {
+                error!("Error searching bots: {}", err);
+                return models::Search::default();
+            }
+        };
+
+        let mut bots_map = IndexMap::new();
+
         for bot in bots_row {
+            bots_map.insert(bot.bot_id, bot);
+        }
+
+        let mut bots = Vec::new();
+        for (bot_id, rank) in bot_matches.page_ids.iter().zip(bot_matches.page_ranks.iter()) {
+            let bot = match bots_map.swap_remove(bot_id) {
+                Some(bot) => bot,
+                None => continue,
+            };
+
             bots.push(models::IndexBot {
                 guild_count: bot.guild_count.unwrap_or_default(),
                 description: bot.description,
@@ -1019,28 +2092,122 @@ impl Database {
                 flags: bot.flags,
                 created_at: bot.created_at,
                 user: self.get_user(bot.bot_id).await,
+                review_stats: Some(self.get_review_stats(bot.bot_id, models::TargetType::Bot).await),
+                search_meta: Some(models::SearchMeta {
+                    rank: *rank,
+                    snippet: converters::highlight_snippet(&bot.snippet),
+                }),
             });
         }
 
-        // Get servers row
+        // Servers work the same way as bots. Servers do not have features, libraries or page styles
+        let server_matches = sqlx::query!(
+            "WITH matches AS MATERIALIZED (
+                SELECT servers.guild_id, servers.votes, servers.guild_count, servers.tags, 
+                servers.state, servers.flags,
+                ts_rank(servers.search_vector || owners.search_vector, query) 
+                + similarity(servers.name_cached, $2) AS rank
+                FROM servers
+                CROSS JOIN to_tsquery('simple', $1) query
+                CROSS JOIN LATERAL (
+                    SELECT setweight(to_tsvector('simple', 
+                    COALESCE(users.username, '') || ' ' || servers.owner_id::text), 'D') AS search_vector 
+                    FROM (SELECT 1) one LEFT JOIN users ON users.user_id = servers.owner_id
+                ) owners
+                WHERE ($2 = '' OR servers.search_vector @@ query OR servers.name_cached % $2 OR servers.owner_id IN (
+                    SELECT users.user_id FROM users WHERE to_tsvector('simple', COALESCE(users.username, '')) @@ query
+                ) OR servers.owner_id::text = $2) 
+                AND (servers.state = $3 OR servers.state = $4)
+                AND (servers.guild_count > $5)
+                AND (($6 = -1::bigint) OR (servers.guild_count < $6))
+                AND ($7::text[] IS NULL OR (CASE WHEN $8 THEN servers.tags && $7 ELSE servers.tags @> $7 END))
+                AND ($9::boolean IS NULL OR ($10 = ANY(servers.flags)) = $9)
+                AND ($11::timestamptz IS NULL OR servers.created_at > $11)
+            ), page AS (
+                SELECT guild_id, rank, row_number() OVER (
+                    ORDER BY rank DESC, votes DESC, guild_count DESC, guild_id
+                ) AS pos FROM matches ORDER BY pos LIMIT $12 OFFSET $13
+            )
+            SELECT (SELECT COUNT(*) FROM matches) AS \"total!\",
+            ARRAY(SELECT guild_id FROM page ORDER BY pos) AS \"page_ids!\",
+            ARRAY(SELECT rank FROM page ORDER BY pos) AS \"page_ranks!\",
+            (
+                SELECT json_object_agg(tag, count ORDER BY count DESC) FROM (
+                    SELECT unnest(tags) AS tag, COUNT(*) AS count FROM matches GROUP BY 1
+                ) tags
+            ) AS tag_facets,
+            (SELECT COUNT(*) FROM matches WHERE state = $14) AS \"certified!\",
+            (SELECT COUNT(*) FROM matches WHERE $10 = ANY(flags)) AS \"nsfw!\"",
+            tsquery,
+            q,
+            state_a as i32,
+            state_b as i32,
+            search.gc_from,
+            search.gc_to,
+            tags.as_deref(),
+            tag_mode_or,
+            search.nsfw,
+            models::Flags::NSFW as i32,
+            search.created_after,
+            per_page,
+            offset,
+            models::State::Certified as i32,
+        )
+        .fetch_one(&self.pool)
+        .await;
+
+        let server_matches = match server_matches {
+            Ok(server_matches) => server_matches,
+            Err(err) => {
+                error!("Error searching servers: {}", err);
+                return models::Search::default();
+            }
+        };
+
+        let server_facets = models::SearchFacets {
+            total: server_matches.total,
+            tags: facet_counts(server_matches.tag_facets),
+            certified: server_matches.certified,
+            nsfw: server_matches.nsfw,
+            ..models::SearchFacets::default()
+        };
+
         let servers_row = sqlx::query!(
-            "SELECT DISTINCT servers.guild_id, servers.created_at,
+            "SELECT servers.guild_id, servers.created_at,
             servers.description, servers.banner_card, servers.state,
-            servers.votes, servers.guild_count, servers.flags FROM servers
-            WHERE (servers.description ilike $1
-            OR servers.long_description ilike $1
-            OR servers.name_cached ilike $1) AND servers.state = $2
-            ORDER BY servers.votes DESC, servers.guild_count DESC LIMIT 6",
-            "%".to_string() + &search.q + "%",
-            models::State::Approved as i32,
+            servers.votes, servers.guild_count, servers.flags,
+            ts_headline('simple', servers.description, query, 
+            'StartSel=[[[, StopSel=]]], MaxWords=35, MinWords=15') AS \"snippet!\"
+            FROM servers CROSS JOIN to_tsquery('simple', $1) query
+            WHERE servers.guild_id = ANY($2)",
+            tsquery,
+            &server_matches.page_ids,
         )
         .fetch_all(&self.pool)
-        .await
-        .unwrap();
+        .await;
 
-        let mut servers = Vec::new();
+        let servers_row = match servers_row {
+            Ok(servers_row) => servers_row,
+            Err(err) => {
+                error!("Error searching servers: {}", err);
+                return models::Search::default();
+            }
+        };
+
+        let mut servers_map = IndexMap::new();
 
         for server in servers_row {
+            servers_map.insert(server.guild_id, server);
+        }
+
+        let mut servers = Vec::new();
+
+        for (guild_id, rank) in server_matches.page_ids.iter().zip(server_matches.page_ranks.iter()) {
+            let server = match servers_map.swap_remove(guild_id) {
+                Some(server) => server,
+                None => continue,
+            };
+
             servers.push(models::IndexBot {
                 guild_count: server.guild_count.unwrap_or(0),
                 description: server.description,
@@ -1052,82 +2219,354 @@ impl Database {
                 flags: server.flags,
                 created_at: server.created_at,
                 user: self.get_server_user(server.guild_id).await,
+                review_stats: Some(self.get_review_stats(server.guild_id, models::TargetType::Server).await),
+                search_meta: Some(models::SearchMeta {
+                    rank: *rank,
+                    snippet: converters::highlight_snippet(&server.snippet),
+                }),
             });
         }
 
-        // Profiles
-        let profiles_row = sqlx::query!(
-            "SELECT DISTINCT users.user_id, users.description FROM users 
-            INNER JOIN bot_owner ON users.user_id = bot_owner.owner 
-            INNER JOIN bots ON bot_owner.bot_id = bots.bot_id 
-            WHERE ((bots.state = 0 OR bots.state = 6) 
-            AND (bots.username_cached ilike $1 OR bots.description ilike $1 OR bots.bot_id::text ilike $1)) 
-            OR (users.username ilike $1) LIMIT 12", 
-            "%".to_string()+&search.q+"%",
+        let (profiles, _) = self.search_profiles(&search.q, per_page, offset).await;
+
+        // Tags
+        let tags = models::SearchTags {
+            bots: self.bot_list_tags().await,
+            servers: self.server_list_tags().await,
+        };
+
+        let (packs, _) = self.search_packs(&search.q, per_page, offset).await;
+
+        models::Search { bots, servers, profiles, packs, tags, facets, server_facets }
+    }
+
+    /// Rebuilds the search suggestion index from the database
+    pub async fn refresh_suggestions(&self) {
+        let mut entries = Vec::new();
+
+        let mut add = |name: &str, vanity: Option<String>, score: i64, id: String, suggestion_type: models::SuggestionType| {
+            let suggestion = models::Suggestion {
+                id,
+                name: name.to_string(),
+                vanity: vanity.clone(),
+                suggestion_type,
+            };
+
+            if let Some(vanity) = vanity {
+                if !vanity.is_empty() {
+                    entries.push((vanity.to_lowercase(), score, suggestion.clone()));
+                }
+            }
+
+            if !name.is_empty() {
+                entries.push((name.to_lowercase(), score, suggestion));
+            }
+        };
+
+        let bots = sqlx::query!(
+            "SELECT bots.bot_id, bots.username_cached, bots.votes, vanity.vanity_url FROM bots 
+            LEFT JOIN vanity ON vanity.redirect = bots.bot_id AND vanity.type = 1
+            WHERE bots.state = $1 OR bots.state = $2",
+            models::State::Approved as i32,
+            models::State::Certified as i32,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        for bot in bots {
+            add(&bot.username_cached, bot.vanity_url, bot.votes.unwrap_or_default(), bot.bot_id.to_string(), models::SuggestionType::Bot);
+        }
+
+        let servers = sqlx::query!(
+            "SELECT servers.guild_id, servers.name_cached, servers.votes, vanity.vanity_url FROM servers 
+            LEFT JOIN vanity ON vanity.redirect = servers.guild_id AND vanity.type = 0
+            WHERE servers.state = $1 OR servers.state = $2",
+            models::State::Approved as i32,
+            models::State::Certified as i32,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        for server in servers {
+            add(&server.name_cached, server.vanity_url, server.votes.unwrap_or_default(), server.guild_id.to_string(), models::SuggestionType::Server);
+        }
+
+        let packs = sqlx::query!(
+            "SELECT id, name, (
+                SELECT COUNT(*) FROM bot_pack_followers WHERE bot_pack_followers.pack_id = bot_packs.id
+            ) AS \"followers!\" FROM bot_packs WHERE visibility = $1",
+            models::PackVisibility::Public as i32,
         )
         .fetch_all(&self.pool)
         .await
-        .unwrap();
+        .unwrap_or_default();
+
+        for pack in packs {
+            add(&pack.name, None, pack.followers, pack.id.to_string(), models::SuggestionType::Pack);
+        }
+
+        let bot_tags = sqlx::query!(
+            "SELECT bot_list_tags.id, COUNT(bot_tags.bot_id) AS \"count!\" FROM bot_list_tags 
+            LEFT JOIN bot_tags ON bot_tags.tag = bot_list_tags.id GROUP BY bot_list_tags.id"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        for tag in bot_tags {
+            add(&tag.id.to_title_case(), None, tag.count, tag.id, models::SuggestionType::BotTag);
+        }
+
+        let server_tags = sqlx::query!("SELECT id, name FROM server_tags")
+            .fetch_all(&self.pool)
+            .await
+            .unwrap_or_default();
+
+        for tag in server_tags {
+            add(&tag.name.to_title_case(), None, 0, tag.id, models::SuggestionType::ServerTag);
+        }
+
+        let index = Arc::new(SuggestIndex::new(entries));
+
+        match self.suggest_index.write() {
+            Ok(mut suggest_index) => *suggest_index = index,
+            Err(err) => error!("Could not update suggestion index: {}", err),
+        }
+    }
+
+    pub fn search_suggest(&self, query: &str, limit: usize) -> Vec<models::Suggestion> {
+        match self.suggest_index.read() {
+            Ok(index) => index.lookup(query, limit),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Searches profiles by username or by the bots they own. Only users owning at least one 
+    /// approved or certified bot are returned
+    /// 
+    /// Returns the profiles on the page and the total amount of matching profiles
+    pub async fn search_profiles(&self, query: &str, limit: i64, offset: i64) -> (Vec<models::SearchProfile>, i64) {
+        let tsquery = converters::to_prefix_tsquery(query);
+        let q = query.trim().to_string();
+
+        // The total is counted before paginating so pages past the end still have it
+        let row = sqlx::query!(
+            "WITH matches AS (
+                SELECT users.user_id, 
+                MAX(GREATEST(similarity(COALESCE(users.username, ''), $2), ts_rank(bots.search_vector, query))) AS rank
+                FROM users
+                INNER JOIN bot_owner ON bot_owner.owner = users.user_id
+                INNER JOIN bots ON bots.bot_id = bot_owner.bot_id
+                CROSS JOIN to_tsquery('simple', $1) query
+                WHERE (bots.state = $3 OR bots.state = $4) 
+                AND ($2 = '' OR users.username % $2 
+                OR to_tsvector('simple', COALESCE(users.username, '')) @@ query
+                OR users.user_id::text = $2 OR bots.bot_id::text = $2
+                OR bots.search_vector @@ query)
+                GROUP BY users.user_id
+            ), page AS (
+                SELECT user_id, row_number() OVER (ORDER BY rank DESC, user_id) AS pos 
+                FROM matches ORDER BY pos LIMIT $5 OFFSET $6
+            )
+            SELECT (SELECT COUNT(*) FROM matches) AS \"total!\",
+            ARRAY(SELECT page.user_id FROM page ORDER BY pos) AS \"user_ids!\",
+            ARRAY(
+                SELECT COALESCE(users.description, '') FROM page 
+                INNER JOIN users ON users.user_id = page.user_id ORDER BY pos
+            ) AS \"descriptions!\"",
+            tsquery,
+            q,
+            models::State::Approved as i32,
+            models::State::Certified as i32,
+            limit,
+            offset,
+        )
+        .fetch_one(&self.pool)
+        .await;
+
+        let row = match row {
+            Ok(row) => row,
+            Err(err) => {
+                error!("Error searching profiles: {}", err);
+                return (Vec::new(), 0);
+            }
+        };
 
         let mut profiles = Vec::new();
 
-        for profile in profiles_row {
+        for (user_id, description) in row.user_ids.into_iter().zip(row.descriptions.into_iter()) {
             profiles.push(models::SearchProfile {
                 banner: "https://api.fateslist.xyz/static/assets/prod/banner.webp".to_string(),
-                description: profile.description.unwrap_or_default(),
-                user: self.get_user(profile.user_id).await,
+                description,
+                user: self.get_user(user_id).await,
             });
         }
 
-        // Tags
-        let tags = models::SearchTags {
-            bots: self.bot_list_tags().await,
-            servers: self.server_list_tags().await,
+        let total = row.total;
+
+        (profiles, total)
+    }
+
+    /// Searches packs by name, description, owner or the bots in them. Only packs containing 
+    /// at least one approved or certified bot are returned
+    /// 
+    /// Returns the packs on the page and the total amount of matching packs
+    pub async fn search_packs(&self, query: &str, limit: i64, offset: i64) -> (Vec<models::BotPack>, i64) {
+        let tsquery = converters::to_prefix_tsquery(query);
+        let q = query.trim().to_string();
+
+        /* Packs matching on their own name/description rank above packs only matching
+        because of a bot in them
+        */
+        let row = sqlx::query!(
+            "WITH matches AS (
+                SELECT bot_packs.id, bot_packs.created_at,
+                ts_rank(bot_packs.search_vector, query) + similarity(bot_packs.name, $2) AS rank
+                FROM bot_packs
+                CROSS JOIN to_tsquery('simple', $1) query
+                WHERE bot_packs.visibility = $7
+                AND EXISTS (
+                    SELECT 1 FROM bot_pack_bots INNER JOIN bots ON bots.bot_id = bot_pack_bots.bot_id 
+                    WHERE bot_pack_bots.pack_id = bot_packs.id AND (bots.state = $3 OR bots.state = $4)
+                )
+                AND ($2 = '' OR bot_packs.search_vector @@ query OR bot_packs.name % $2
+                OR bot_packs.owner::text = $2
+                OR EXISTS (SELECT 1 FROM users WHERE users.user_id = bot_packs.owner AND users.username % $2)
+                OR EXISTS (
+                    SELECT 1 FROM bot_pack_bots INNER JOIN bots ON bots.bot_id = bot_pack_bots.bot_id 
+                    WHERE bot_pack_bots.pack_id = bot_packs.id AND (bots.state = $3 OR bots.state = $4)
+                    AND (bots.search_vector @@ query OR bots.bot_id::text = $2)
+                ))
+            )
+            SELECT (SELECT COUNT(*) FROM matches) AS \"total!\",
+            ARRAY(
+                SELECT id FROM matches ORDER BY rank DESC, created_at DESC, id LIMIT $5 OFFSET $6
+            ) AS \"ids!\"",
+            tsquery,
+            q,
+            models::State::Approved as i32,
+            models::State::Certified as i32,
+            limit,
+            offset,
+            models::PackVisibility::Public as i32,
+        )
+        .fetch_one(&self.pool)
+        .await;
+
+        let row = match row {
+            Ok(row) => row,
+            Err(err) => {
+                error!("Error searching packs: {}", err);
+                return (Vec::new(), 0);
+            }
         };
 
-        // Packs
-        let packs_row = sqlx::query!(
-            "SELECT DISTINCT bot_packs.id, bot_packs.icon, bot_packs.banner, 
-            bot_packs.created_at, bot_packs.owner, bot_packs.bots, 
-            bot_packs.description, bot_packs.name FROM (
-                SELECT id, icon, banner, 
-                created_at, owner, bots, 
-                description, name, unnest(bots) AS bot_id FROM bot_packs
-            ) bot_packs
-            INNER JOIN bots ON bots.bot_id = bot_packs.bot_id 
-            INNER JOIN users ON users.user_id = bot_packs.owner
-            WHERE bot_packs.name ilike $1 OR bot_packs.owner::text 
-            ilike $1 OR users.username ilike $1 OR bots.bot_id::text ilike $1 
-            OR bots.username_cached ilike $1",
-            "%".to_string() + &search.q + "%",
+        let mut packs = Vec::new();
+
+        for id in row.ids {
+            if let Some(pack) = self.get_pack(&id.to_string()).await {
+                packs.push(pack);
+            }
+        }
+
+        let total = row.total;
+
+        (packs, total)
+    }
+
+    /// Searches the commands of approved and certified bots. Commands named exactly like the 
+    /// query come first, the rest is ranked by the votes of their bot
+    pub async fn search_commands(
+        &self,
+        search: &models::CommandSearchQuery,
+        limit: i64,
+        offset: i64,
+    ) -> (Vec<models::CommandSearchResult>, i64) {
+        // People search for slash commands as ``/poll``
+        let q = search.q.trim().trim_start_matches('/').trim().to_lowercase();
+        let tsquery = converters::to_prefix_tsquery(&q);
+
+        let rows = sqlx::query!(
+            "SELECT bot_commands.id, bot_commands.cmd_type, bot_commands.description, 
+            bot_commands.args, bot_commands.examples, bot_commands.premium_only, 
+            bot_commands.notes, bot_commands.doc_link, bot_commands.groups, bot_commands.name, 
+            bot_commands.vote_locked, bot_commands.nsfw, bots.bot_id, bots.created_at,
+            bots.description AS bot_description, bots.banner_card AS banner, bots.state, 
+            bots.votes, bots.flags, bots.guild_count, COUNT(*) OVER() AS \"total!\"
+            FROM bot_commands
+            INNER JOIN bots ON bots.bot_id = bot_commands.bot_id
+            CROSS JOIN to_tsquery('simple', $1) query
+            WHERE (bots.state = $3 OR bots.state = $4)
+            AND ($2 = '' OR strpos(lower(bot_commands.name), $2) > 0 
+            OR to_tsvector('simple', bot_commands.description) @@ query)
+            AND ($5::integer IS NULL OR bot_commands.cmd_type = $5)
+            AND (COALESCE(bot_commands.nsfw, false) OR $7 = ANY(bots.flags)) = $6
+            AND ($8::boolean IS NULL OR bot_commands.premium_only = $8)
+            AND ($9::boolean IS NULL OR bot_commands.vote_locked = $9)
+            ORDER BY (lower(bot_commands.name) = $2) DESC, bots.votes DESC NULLS LAST, 
+            bot_commands.name LIMIT $10 OFFSET $11",
+            tsquery,
+            q,
+            models::State::Approved as i32,
+            models::State::Certified as i32,
+            search.cmd_type.map(|cmd_type| cmd_type as i32),
+            search.nsfw.unwrap_or(false),
+            models::Flags::NSFW as i32,
+            search.premium_only,
+            search.vote_locked,
+            limit,
+            offset,
         )
         .fetch_all(&self.pool)
-        .await
-        .unwrap();
+        .await;
 
-        let mut packs = Vec::new();
+        let rows = match rows {
+            Ok(rows) => rows,
+            Err(err) => {
+                error!("Error searching commands: {}", err);
+                return (Vec::new(), 0);
+            }
+        };
 
-        for pack in packs_row {
-            packs.push(models::BotPack {
-                id: pack.id.to_string(),
-                name: pack.name.to_string(),
-                description: pack.description,
-                icon: pack.icon.unwrap_or_default(),
-                banner: pack.banner.unwrap_or_else(|| {
-                    "https://api.fateslist.xyz/static/assets/prod/banner.webp".to_string()
-                }),
-                owner: self.get_user(pack.owner).await,
-                created_at: pack.created_at.unwrap_or_else(|| {
-                    chrono::DateTime::<chrono::Utc>::from_utc(
-                        chrono::NaiveDateTime::from_timestamp(0, 0),
-                        chrono::Utc,
-                    )
-                }),
-                resolved_bots: self.resolve_pack_bots(pack.bots).await,
+        let total = rows.first().map_or(0, |row| row.total);
+
+        let mut results = Vec::new();
+
+        for row in rows {
+            results.push(models::CommandSearchResult {
+                command: models::BotCommand {
+                    id: Some(row.id.to_string()),
+                    nsfw: row.nsfw.unwrap_or(false),
+                    cmd_type: models::CommandType::try_from(row.cmd_type)
+                        .unwrap_or(models::CommandType::SlashCommandGlobal),
+                    description: row.description,
+                    args: serde_json::from_value(row.args).unwrap_or_default(),
+                    examples: row.examples,
+                    premium_only: row.premium_only,
+                    notes: row.notes,
+                    doc_link: row.doc_link,
+                    name: row.name,
+                    vote_locked: row.vote_locked,
+                    groups: row.groups,
+                },
+                bot: models::IndexBot {
+                    guild_count: row.guild_count.unwrap_or_default(),
+                    description: row.bot_description,
+                    banner: row.banner.unwrap_or_default(),
+                    votes: row.votes.unwrap_or_default(),
+                    state: models::State::try_from(row.state).unwrap_or(models::State::Approved),
+                    flags: row.flags,
+                    created_at: row.created_at,
+                    user: self.get_user(row.bot_id).await,
+                    review_stats: None,
+                    search_meta: None,
+                },
             });
         }
 
-        models::Search { bots, servers, profiles, packs, tags }
+        (results, total)
     }
 
     // Search bot/server tags
@@ -1160,13 +2599,16 @@ impl Database {
                 votes: row.votes.unwrap_or(0),
                 flags: row.flags,
                 user: self.get_user(row.bot_id).await,
+                review_stats: Some(self.get_review_stats(row.bot_id, models::TargetType::Bot).await),
+                search_meta: None,
             });
         }
 
         let server_rows = sqlx::query!(
             "SELECT DISTINCT guild_id, flags, created_at, 
             description, state, banner_card, votes, guild_count FROM servers 
-            WHERE state = 0 AND tags && $1",
+            WHERE (state = 0 OR state = 6) AND tags && $1 
+            ORDER BY votes DESC LIMIT 15",
             &vec![tag.clone()]
         )
         .fetch_all(&self.pool)
@@ -1187,6 +2629,8 @@ impl Database {
                 votes: row.votes.unwrap_or(0),
                 flags: row.flags,
                 user: self.get_server_user(row.guild_id).await,
+                review_stats: Some(self.get_review_stats(row.guild_id, models::TargetType::Server).await),
+                search_meta: None,
             });
         }
 
@@ -1199,45 +2643,80 @@ impl Database {
             },
             profiles: Vec::new(), // Not applicable
             packs: Vec::new(),    // Not applicable
+            facets: models::SearchFacets::default(), // Not applicable
+            server_facets: models::SearchFacets::default(), // Not applicable
         }
     }
 
-    #[async_recursion]
-    pub async fn random_bot(&self) -> models::IndexBot {
-        let random_row = sqlx::query!(
-            "SELECT description, banner_card, state, votes, created_at, guild_count, bot_id, flags 
-            FROM bots WHERE (state = 0 OR state = 6) ORDER BY RANDOM() LIMIT 1"
-        )
-        .fetch_one(&self.pool)
-        .await
-        .unwrap();
-        let index_bot = models::IndexBot {
-            description: random_row.description,
-            banner: random_row.banner_card.unwrap_or_else(|| {
-                "https://api.fateslist.xyz/static/assets/prod/banner.webp".to_string()
-            }),
-            state: models::State::try_from(random_row.state).unwrap_or(models::State::Approved),
-            votes: random_row.votes.unwrap_or(0),
-            guild_count: random_row.guild_count.unwrap_or(0),
-            user: self.get_user(random_row.bot_id).await,
-            flags: random_row.flags,
-            created_at: random_row.created_at,
-        };
-        if index_bot.user.username.starts_with("Deleted") || index_bot.flags.contains(&(models::Flags::NSFW as i32)) {
-            return self.random_bot().await;
+    /// NSFW bots are only returned if ``nsfw`` is set. Bots of deleted users are skipped (up 
+    /// to a few times), ``None`` means no bot could be found
+    pub async fn random_bot(&self, nsfw: bool) -> Option<models::IndexBot> {
+        for _ in 0..5 {
+            let random_row = sqlx::query!(
+                "SELECT description, banner_card, state, votes, created_at, guild_count, bot_id, flags 
+                FROM bots WHERE (state = 0 OR state = 6) AND ($1 OR NOT ($2 = ANY(flags))) 
+                ORDER BY RANDOM() LIMIT 1",
+                nsfw,
+                models::Flags::NSFW as i32,
+            )
+            .fetch_optional(&self.pool)
+            .await;
+
+            let random_row = match random_row {
+                Ok(Some(row)) => row,
+                Ok(None) => return None,
+                Err(err) => {
+                    error!("Error getting random bot: {}", err);
+                    return None;
+                }
+            };
+
+            let user = self.get_user(random_row.bot_id).await;
+
+            if user.username.starts_with("Deleted") {
+                continue;
+            }
+
+            return Some(models::IndexBot {
+                description: random_row.description,
+                banner: random_row.banner_card.unwrap_or_else(|| {
+                    "https://api.fateslist.xyz/static/assets/prod/banner.webp".to_string()
+                }),
+                state: models::State::try_from(random_row.state).unwrap_or(models::State::Approved),
+                votes: random_row.votes.unwrap_or(0),
+                guild_count: random_row.guild_count.unwrap_or(0),
+                user,
+                review_stats: Some(self.get_review_stats(random_row.bot_id, models::TargetType::Bot).await),
+                search_meta: None,
+                flags: random_row.flags,
+                created_at: random_row.created_at,
+            });
         }
-        index_bot
+
+        None
     }
 
-    pub async fn random_server(&self) -> models::IndexBot {
+    /// NSFW servers are only returned if ``nsfw`` is set
+    pub async fn random_server(&self, nsfw: bool) -> Option<models::IndexBot> {
         let random_row = sqlx::query!(
             "SELECT description, banner_card, state, votes, guild_count, guild_id, flags, created_at FROM servers 
-            WHERE (state = 0 OR state = 6) ORDER BY RANDOM() LIMIT 1"
+            WHERE (state = 0 OR state = 6) AND ($1 OR NOT ($2 = ANY(flags))) 
+            ORDER BY RANDOM() LIMIT 1",
+            nsfw,
+            models::Flags::NSFW as i32,
         )
-        .fetch_one(&self.pool)
-        .await
-        .unwrap();
-        let index_bot = models::IndexBot {
+        .fetch_optional(&self.pool)
+        .await;
+
+        let random_row = match random_row {
+            Ok(row) => row?,
+            Err(err) => {
+                error!("Error getting random server: {}", err);
+                return None;
+            }
+        };
+
+        Some(models::IndexBot {
             description: random_row.description,
             banner: random_row.banner_card.unwrap_or_else(|| {
                 "https://api.fateslist.xyz/static/assets/prod/banner.webp".to_string()
@@ -1246,15 +2725,11 @@ impl Database {
             votes: random_row.votes.unwrap_or(0),
             guild_count: random_row.guild_count.unwrap_or(0),
             user: self.get_server_user(random_row.guild_id).await,
+            review_stats: Some(self.get_review_stats(random_row.guild_id, models::TargetType::Server).await),
+            search_meta: None,
             flags: random_row.flags,
             created_at: random_row.created_at,
-        };
-
-        if index_bot.flags.contains(&(models::Flags::NSFW as i32)) {
-            return self.random_bot().await;
-        }
-
-        index_bot
+        })
     }
 
     pub async fn ws_event<T: 'static + Serialize + Clone + Sync>(&self, event: models::Event<T>) {
@@ -1453,47 +2928,63 @@ impl Database {
     }
 
     /// Posts bot stats
+    /// Posts stats of a bot. ``app`` is ``None`` when application info is unavailable, the stats
+    /// are then stored pending verification (see ``verify_pending_stats``)
     pub async fn post_stats(
         &self,
         bot_id: i64,
-        client_id: i64,
         stats: models::BotStats,
-        japi_key: &str,
+        app: Option<models::ApplicationInfo>,
     ) -> Result<(), models::StatsError> {
-        // Next check server count
-        let resp = self
-            .requests
-            .get(format!(
-                "https://japi.rest/discord/v1/application/{bot_id}",
-                bot_id = client_id
-            ))
-            .timeout(Duration::from_secs(10))
-            .header("Authorization", japi_key)
-            .send()
-            .await
-            .map_err(models::StatsError::JAPIError)?;
-    
-        let status = resp.status();
-        if !status.is_success() {
-            return Err(models::StatsError::ClientIDNeeded);
-        }    
-
-        let resp_json: models::JAPIApplication = resp
-            .json()
-            .await
-            .map_err(models::StatsError::JAPIDeserError)?;
-
-        let approx_count = resp_json.data.bot.approximate_guild_count;
+        let pending = app.is_none();
+        let approx_count = app.and_then(|app| app.approximate_guild_count);
 
         // Now perform some basic sanity checks
         if stats.guild_count < 0 {
             return Err(models::StatsError::BadStats(
                 "Server count cannot be less than 0".to_string(),
             ));
-        } else if stats.guild_count < 100 && (approx_count - stats.guild_count) > 100 {
-            return Err(models::StatsError::BadStats(
-                "Server count is way too high! This bot only has a approximate guild count of".to_string() + &approx_count.to_string(),
-            ));
+        }
+
+        if let Some(approx_count) = approx_count {
+            if stats.guild_count < 100 && (approx_count - stats.guild_count) > 100 {
+                return Err(models::StatsError::BadStats(
+                    "Server count is way too high! This bot only has a approximate guild count of".to_string() + &approx_count.to_string(),
+                ));
+            }
+        }
+
+        if let Some(ref shard_guilds) = stats.shard_guilds {
+            let valid = shard_guilds.iter().all(|count| *count >= 0)
+                && shard_guilds.iter().sum::<i64>() == stats.guild_count
+                && stats.shard_count.map_or(true, |count| shard_guilds.len() as i64 <= count);
+
+            if !valid {
+                return Err(models::StatsError::ShardGuildsMismatch);
+            }
+        }
+
+        let mut tx = self.pool.begin().await.map_err(models::StatsError::SQLError)?;
+
+        // The last post is what is currently stored on the bot. The row stays locked until the
+        // post is stored so concurrent posts are checked against each other
+        let last = sqlx::query!(
+            "SELECT guild_count, last_stats_post FROM bots WHERE bot_id = $1 FOR UPDATE",
+            bot_id
+        )
+        .fetch_one(&mut tx)
+        .await
+        .map_err(models::StatsError::SQLError)?;
+
+        let last = last.guild_count
+            .filter(|count| *count > 0)
+            .map(|count| (count, last.last_stats_post));
+
+        let anomaly = detect_stats_anomaly(stats.guild_count, approx_count, last)
+            .map_err(models::StatsError::Anomaly)?;
+
+        if let Some(ref anomaly) = anomaly {
+            info!("Stats anomaly for {}: {}", bot_id, anomaly);
         }
 
         // Shard count
@@ -1504,7 +2995,7 @@ impl Database {
                     count,
                     bot_id
                 )
-                .execute(&self.pool)
+                .execute(&mut tx)
                 .await
                 .map_err(models::StatsError::SQLError)?;
             }
@@ -1520,7 +3011,7 @@ impl Database {
                     count,
                     bot_id
                 )
-                .execute(&self.pool)
+                .execute(&mut tx)
                 .await
                 .map_err(models::StatsError::SQLError)?;
             }
@@ -1537,7 +3028,7 @@ impl Database {
                     count_ref,
                     bot_id
                 )
-                .execute(&self.pool)
+                .execute(&mut tx)
                 .await
                 .map_err(models::StatsError::SQLError)?;
             }
@@ -1551,12 +3042,163 @@ impl Database {
             stats.guild_count,
             bot_id,
         )
-        .execute(&self.pool)
+        .execute(&mut tx)
+        .await
+        .map_err(models::StatsError::SQLError)?;
+
+        sqlx::query!(
+            "INSERT INTO bot_stats_history (bot_id, guild_count, shard_count, user_count, shard_guilds, anomaly, pending_verification) 
+            VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            bot_id,
+            stats.guild_count,
+            stats.shard_count,
+            stats.user_count,
+            stats.shard_guilds.as_deref(),
+            anomaly,
+            pending,
+        )
+        .execute(&mut tx)
         .await
         .map_err(models::StatsError::SQLError)?;
+
+        tx.commit().await.map_err(models::StatsError::SQLError)?;
+
+        Ok(())
+    }
+
+    /// Bots (and their client ids) with stats from the last week that are pending verification
+    pub async fn get_pending_stats_bots(&self) -> Vec<(i64, i64)> {
+        let rows = sqlx::query!(
+            "SELECT DISTINCT bots.bot_id, bots.client_id FROM bot_stats_history 
+            INNER JOIN bots ON bots.bot_id = bot_stats_history.bot_id 
+            WHERE bot_stats_history.pending_verification 
+            AND bot_stats_history.posted_at > NOW() - INTERVAL '7 days'"
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        match rows {
+            Ok(rows) => rows
+                .into_iter()
+                // Older bots have no client id as it is the same as the bot id
+                .map(|row| (row.bot_id, row.client_id.unwrap_or(row.bot_id)))
+                .collect(),
+            Err(err) => {
+                error!("Error getting pending stats: {}", err);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Checks stats that were accepted pending verification against the approximate guild count.
+    /// Posts far from it are marked as anomalies and if the current guild count is one of them,
+    /// it is replaced by the approximate guild count
+    pub async fn verify_pending_stats(&self, bot_id: i64, approx_count: Option<i64>) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        if let Some(approx_count) = approx_count {
+            let anomaly = format!("Guild count is far from the approximate guild count of {} (verified later)", approx_count);
+
+            let rows = sqlx::query!(
+                "SELECT guild_count, posted_at FROM bot_stats_history WHERE bot_id = $1 AND pending_verification",
+                bot_id
+            )
+            .fetch_all(&mut tx)
+            .await?;
+
+            for row in rows.iter().filter(|row| off_approx(row.guild_count, approx_count)) {
+                sqlx::query!(
+                    "UPDATE bot_stats_history SET anomaly = COALESCE(anomaly || '. ', '') || $3 
+                    WHERE bot_id = $1 AND posted_at = $2",
+                    bot_id,
+                    row.posted_at,
+                    anomaly,
+                )
+                .execute(&mut tx)
+                .await?;
+            }
+
+            let current = sqlx::query!("SELECT guild_count FROM bots WHERE bot_id = $1", bot_id)
+                .fetch_one(&mut tx)
+                .await?;
+
+            if current.guild_count.map_or(false, |count| off_approx(count, approx_count)) {
+                info!("Reverting unverified guild count of {} to {}", bot_id, approx_count);
+                sqlx::query!("UPDATE bots SET guild_count = $1 WHERE bot_id = $2", approx_count, bot_id)
+                    .execute(&mut tx)
+                    .await?;
+            }
+        }
+
+        sqlx::query!(
+            "UPDATE bot_stats_history SET pending_verification = false WHERE bot_id = $1 AND pending_verification",
+            bot_id
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.bot_cache.invalidate(&bot_id).await;
+
         Ok(())
     }
 
+    /// Gets the stats history of a bot with one point per hour or day (the last post in it)
+    pub async fn get_stats_history(&self, bot_id: i64, range: models::StatsRange) -> models::StatsHistory {
+        let rows = sqlx::query!(
+            "SELECT DISTINCT ON (1) date_trunc($3, posted_at) AS \"ts!\", guild_count, 
+            shard_count, user_count, shard_guilds, anomaly FROM bot_stats_history 
+            WHERE bot_id = $1 AND posted_at > NOW() - make_interval(days => $2) 
+            ORDER BY 1, posted_at DESC",
+            bot_id,
+            range.days(),
+            range.bucket(),
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        let points: Vec<models::StatsPoint> = match rows {
+            Ok(rows) => rows
+                .into_iter()
+                .map(|row| models::StatsPoint {
+                    ts: row.ts,
+                    guild_count: row.guild_count,
+                    shard_count: row.shard_count,
+                    user_count: row.user_count,
+                    shard_guilds: row.shard_guilds,
+                    anomaly: row.anomaly,
+                })
+                .collect(),
+            Err(err) => {
+                error!("Error getting stats history: {}", err);
+                Vec::new()
+            }
+        };
+
+        let growth = match (points.first(), points.last()) {
+            (Some(first), Some(last)) => last.guild_count - first.guild_count,
+            _ => 0,
+        };
+
+        models::StatsHistory {
+            range,
+            points,
+            growth,
+        }
+    }
+
+    /// Stats history is kept for a year
+    pub async fn prune_stats_history(&self) {
+        let res = sqlx::query!("DELETE FROM bot_stats_history WHERE posted_at < NOW() - INTERVAL '366 days'")
+            .execute(&self.pool)
+            .await;
+
+        if let Err(err) = res {
+            error!("Error pruning stats history: {}", err);
+        }
+    }
+
     /// Calls get bot and then fills in `api_token`, `webhook` and `webhook_secret`
     pub async fn get_bot_settings(
         &self,
@@ -2121,54 +3763,160 @@ impl Database {
         None
     }
 
-    pub async fn add_pack(&self, pack: models::BotPack) -> Result<(), models::PackCheckError> {
-        // Get bots from the pack
-        let mut bots = Vec::new();
-        for bot in pack.resolved_bots {
-            let parsed_id = bot.user.id.parse::<i64>();
-            if parsed_id.is_err() {
-                return Err(models::PackCheckError::InvalidBotId);
+    pub async fn get_pack_editors(&self, pack_id: &str) -> Vec<i64> {
+        let pack_id_uuid = uuid::Uuid::parse_str(pack_id);
+
+        if let Ok(id) = pack_id_uuid {
+            let rows = sqlx::query!("SELECT user_id FROM bot_pack_editors WHERE pack_id = $1", id)
+                .fetch_all(&self.pool)
+                .await
+                .unwrap_or_default();
+
+            return rows.into_iter().map(|row| row.user_id).collect();
+        }
+        Vec::new()
+    }
+
+    /// Follows a pack, returns false if the pack does not exist
+    pub async fn follow_pack(&self, pack_id: &str, user_id: i64) -> Result<bool, sqlx::Error> {
+        let id = match uuid::Uuid::parse_str(pack_id) {
+            Ok(id) => id,
+            Err(_) => return Ok(false),
+        };
+
+        let res = sqlx::query!(
+            "INSERT INTO bot_pack_followers (pack_id, user_id) 
+            SELECT id, $2 FROM bot_packs WHERE id = $1 ON CONFLICT DO NOTHING",
+            id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Already following is not an error
+        if res.rows_affected() > 0 {
+            return Ok(true);
+        }
+
+        let exists = sqlx::query!("SELECT id FROM bot_packs WHERE id = $1", id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(exists.is_some())
+    }
+
+    /// Unfollows a pack, returns false if the user was not following it
+    pub async fn unfollow_pack(&self, pack_id: &str, user_id: i64) -> Result<bool, sqlx::Error> {
+        let id = match uuid::Uuid::parse_str(pack_id) {
+            Ok(id) => id,
+            Err(_) => return Ok(false),
+        };
+
+        let res = sqlx::query!(
+            "DELETE FROM bot_pack_followers WHERE pack_id = $1 AND user_id = $2",
+            id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Replaces the bots (in order) and optionally the editors of a pack
+    async fn write_pack_members(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        pack_id: uuid::Uuid,
+        pack: &models::BotPack,
+        update_editors: bool,
+    ) -> Result<(), models::PackCheckError> {
+        sqlx::query!("DELETE FROM bot_pack_bots WHERE pack_id = $1", pack_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(models::PackCheckError::SQLError)?;
+
+        for (position, bot) in pack.resolved_bots.iter().enumerate() {
+            let bot_id = bot.user.id.parse::<i64>().map_err(|_| models::PackCheckError::InvalidBotId)?;
+
+            sqlx::query!(
+                "INSERT INTO bot_pack_bots (pack_id, bot_id, position, note) VALUES ($1, $2, $3, $4)",
+                pack_id,
+                bot_id,
+                position as i32,
+                bot.note,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(models::PackCheckError::SQLError)?;
+        }
+
+        if update_editors {
+            sqlx::query!("DELETE FROM bot_pack_editors WHERE pack_id = $1", pack_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(models::PackCheckError::SQLError)?;
+
+            for editor in &pack.editors {
+                let user_id = editor.id.parse::<i64>().map_err(|_| models::PackCheckError::InvalidEditor)?;
+
+                sqlx::query!(
+                    "INSERT INTO bot_pack_editors (pack_id, user_id) VALUES ($1, $2)",
+                    pack_id,
+                    user_id,
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err(models::PackCheckError::SQLError)?;
             }
-            bots.push(parsed_id.unwrap())
         }
 
-        sqlx::query!(
-            "INSERT INTO bot_packs (icon, banner, owner, bots, description, name) VALUES ($1, $2, $3, $4, $5, $6)",
+        Ok(())
+    }
+
+    pub async fn add_pack(&self, pack: models::BotPack) -> Result<(), models::PackCheckError> {
+        let mut tx = self.pool.begin().await.map_err(models::PackCheckError::SQLError)?;
+
+        let row = sqlx::query!(
+            "INSERT INTO bot_packs (icon, banner, owner, description, name, visibility) 
+            VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
             pack.icon, pack.banner, 
-            pack.owner.id.parse::<i64>().unwrap(), &bots, 
-            pack.description, pack.name
+            pack.owner.id.parse::<i64>().unwrap(),
+            pack.description, pack.name,
+            pack.visibility as i32,
         )
-        .execute(&self.pool)
+        .fetch_one(&mut tx)
         .await
         .map_err(models::PackCheckError::SQLError)?;
 
+        Self::write_pack_members(&mut tx, row.id, &pack, true).await?;
+
+        tx.commit().await.map_err(models::PackCheckError::SQLError)?;
+
         Ok(())
     }
 
-    pub async fn edit_pack(&self, pack: models::BotPack) -> Result<(), models::PackCheckError> {
-        // Get bots from the pack
-        let mut bots = Vec::new();
-        for bot in pack.resolved_bots {
-            let parsed_id = bot.user.id.parse::<i64>();
-            if parsed_id.is_err() {
-                return Err(models::PackCheckError::InvalidBotId);
-            }
-            bots.push(parsed_id.unwrap());
-        }
-
+    /// Edits a pack. Editors are only changed if ``update_editors`` is set (the owner is editing)
+    pub async fn edit_pack(&self, pack: models::BotPack, update_editors: bool) -> Result<(), models::PackCheckError> {
         let pack_id_uuid = uuid::Uuid::parse_str(&pack.id);
 
         if let Ok(id) = pack_id_uuid {
+            let mut tx = self.pool.begin().await.map_err(models::PackCheckError::SQLError)?;
+
             sqlx::query!(
-                "UPDATE bot_packs SET icon = $1, banner = $2, bots = $3, description = $4, name = $5 WHERE id = $6",
+                "UPDATE bot_packs SET icon = $1, banner = $2, description = $3, name = $4, 
+                visibility = $5 WHERE id = $6",
                 pack.icon, pack.banner,
-                &bots, pack.description,
-                pack.name, id
+                pack.description, pack.name,
+                pack.visibility as i32, id
             )
-            .execute(&self.pool)
+            .execute(&mut tx)
             .await
             .map_err(models::PackCheckError::SQLError)?;
 
+            Self::write_pack_members(&mut tx, id, &pack, update_editors).await?;
+
+            tx.commit().await.map_err(models::PackCheckError::SQLError)?;
+
             Ok(())
         } else {
             Err(models::PackCheckError::InvalidPackId)
@@ -2246,10 +3994,11 @@ impl Database {
         flags
     }
 
-    pub async fn get_profile(&self, user_id: i64) -> Option<models::Profile> {
+    /// Gets the profile of a user. Unlisted packs are only included when the owner is viewing
+    pub async fn get_profile(&self, user_id: i64, include_unlisted: bool) -> Option<models::Profile> {
         let row = sqlx::query!(
             "SELECT flags, description, site_lang, state, user_css, profile_css, extra_links,
-            vote_reminder_channel::text, experiments FROM users WHERE user_id = $1",
+            vote_reminder_channel::text, experiments, show_nsfw FROM users WHERE user_id = $1",
             user_id
         )
         .fetch_one(&self.pool)
@@ -2264,9 +4013,12 @@ impl Database {
         // Fetch enabled experiments
         let user_experiments = self.get_user_experiments(user_id).await;
 
+        // Unlisted packs are otherwise only reachable by their id
         let packs_row = sqlx::query!(
-            "SELECT id, icon, banner, created_at, owner, bots, description, name FROM bot_packs WHERE owner = $1",
-            user_id
+            "SELECT id FROM bot_packs WHERE owner = $1 AND ($3 OR visibility = $2) ORDER BY created_at",
+            user_id,
+            models::PackVisibility::Public as i32,
+            include_unlisted,
         )
         .fetch_all(&self.pool)
         .await
@@ -2274,23 +4026,9 @@ impl Database {
 
         let mut packs = Vec::new();
         for pack in packs_row {
-            packs.push(models::BotPack {
-                id: pack.id.to_string(),
-                name: pack.name.to_string(),
-                description: pack.description,
-                icon: pack.icon.unwrap_or_default(),
-                banner: pack.banner.unwrap_or_else(|| {
-                    "https://api.fateslist.xyz/static/assets/prod/banner.webp".to_string()
-                }),
-                owner: self.get_user(pack.owner).await,
-                created_at: pack.created_at.unwrap_or_else(|| {
-                    chrono::DateTime::<chrono::Utc>::from_utc(
-                        chrono::NaiveDateTime::from_timestamp(0, 0),
-                        chrono::Utc,
-                    )
-                }),
-                resolved_bots: self.resolve_pack_bots(pack.bots).await,
-            });
+            if let Some(pack) = self.get_pack(&pack.id.to_string()).await {
+                packs.push(pack);
+            }
         }
 
         let bots_row = sqlx::query!(
@@ -2318,6 +4056,8 @@ impl Database {
                 flags: row.flags,
                 created_at: row.created_at,
                 user: self.get_user(row.bot_id).await,
+                review_stats: Some(self.get_review_stats(row.bot_id, models::TargetType::Bot).await),
+                search_meta: None,
             };
             bots.push(bot);
         }
@@ -2416,9 +4156,21 @@ impl Database {
             user_css: row.user_css.unwrap_or_default(),
             profile_css: row.profile_css,
             site_lang: row.site_lang.unwrap_or_else(|| "en".to_string()),
+            show_nsfw: Some(row.show_nsfw),
         })
     }
 
+    pub async fn get_user_show_nsfw(&self, user_id: i64) -> bool {
+        sqlx::query!(
+            "SELECT show_nsfw FROM users WHERE user_id = $1",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map(|row| row.show_nsfw)
+        .unwrap_or(false)
+    }
+
     pub async fn update_profile(
         &self,
         user_id: i64,
@@ -2439,13 +4191,14 @@ impl Database {
 
         sqlx::query!(
             "UPDATE users SET description = $1, site_lang = $2, 
-            flags = $3, user_css = $4, profile_css = $5 
-            WHERE user_id = $6",
+            flags = $3, user_css = $4, profile_css = $5, show_nsfw = COALESCE($6, show_nsfw) 
+            WHERE user_id = $7",
             profile.description,
             profile.site_lang,
             &profile.flags,
             profile.user_css,
             profile.profile_css,
+            profile.show_nsfw,
             user_id
         )
         .execute(&self.pool)
@@ -2539,11 +4292,17 @@ impl Database {
 
     // Reviews
     #[async_recursion]
-    async fn get_review_replies(&self, parent_id: uuid::Uuid) -> Vec<models::Review> {
+    async fn get_review_replies(&self, parent_id: uuid::Uuid, depth: i64) -> Vec<models::Review> {
+        // Deep threads are cut off, clients can fetch the rest using Get Single Review
+        if depth >= MAX_REVIEW_REPLY_DEPTH {
+            return Vec::new();
+        }
+
         let rows = sqlx::query!(
             "SELECT id, user_id, star_rating, epoch, review_text, flagged FROM reviews 
-            WHERE parent_id = $1",
+            WHERE parent_id = $1 ORDER BY created_at ASC, id ASC LIMIT $2",
             parent_id,
+            MAX_REVIEW_REPLIES,
         )
         .fetch_all(&self.pool)
         .await
@@ -2560,7 +4319,7 @@ impl Database {
                 votes: self.get_review_votes(row.id).await,
                 review_text: row.review_text,
                 flagged: row.flagged,
-                replies: self.get_review_replies(row.id).await,
+                replies: self.get_review_replies(row.id, depth + 1).await,
                 parent_id: Some(parent_id),
             });
         }
@@ -2568,34 +4327,75 @@ impl Database {
         reviews
     }
 
+    /// Gets the root reviews of a entity. ``cursor`` takes priority over ``offset``
+    /// 
+    /// Returns the reviews along with the cursor for the next page (if there is one)
     pub async fn get_reviews(
         &self,
         target_id: i64,
-        target_type: models::TargetType,
+        query: &models::ReviewQuery,
+        cursor: Option<&models::ReviewCursor>,
         limit: i64,
         offset: i64,
-    ) -> Vec<models::Review> {
+    ) -> (Vec<models::Review>, Option<models::ReviewCursor>) {
         let mut reviews = Vec::new();
 
-        let target_type_num = match target_type {
+        let target_type_num = match query.target_type {
             models::TargetType::Bot => 0,
             models::TargetType::Server => 1,
         };
 
-        // trim(stringexpression) != ''
+        let sort = query.sort.unwrap_or_default();
+
+        /* Every sort is mapped to a single descending sort_key so that (sort_key, id)
+        can be used as a stable keyset cursor regardless of the sort chosen
+        */
         let rows = sqlx::query!(
-            "SELECT id, user_id, star_rating, epoch, review_text, flagged FROM reviews 
-            WHERE target_id = $1 AND target_type = $2 AND parent_id IS NULL 
-            LIMIT $3 OFFSET $4",
+            "SELECT id, user_id, star_rating, epoch, review_text, flagged, sort_key AS \"sort_key!\" FROM (
+                SELECT reviews.id, reviews.user_id, reviews.star_rating, reviews.epoch, 
+                reviews.review_text, reviews.flagged, CASE $3::integer
+                    WHEN 1 THEN -extract(epoch from reviews.created_at)::numeric
+                    WHEN 2 THEN COALESCE((
+                        SELECT SUM(CASE WHEN review_votes.upvote THEN 1 ELSE -1 END) 
+                        FROM review_votes WHERE review_votes.id = reviews.id
+                    ), 0)::numeric
+                    WHEN 3 THEN reviews.star_rating::numeric
+                    WHEN 4 THEN -reviews.star_rating::numeric
+                    ELSE extract(epoch from reviews.created_at)::numeric
+                END AS sort_key FROM reviews
+                WHERE reviews.target_id = $1 AND reviews.target_type = $2 
+                AND reviews.parent_id IS NULL
+                AND ($4::integer IS NULL OR reviews.star_rating >= $4)
+                AND ($5::integer IS NULL OR reviews.star_rating <= $5)
+            ) reviews 
+            WHERE ($6::numeric IS NULL OR (sort_key, id) < ($6, $7::uuid))
+            ORDER BY sort_key DESC, id DESC LIMIT $8 OFFSET $9",
             target_id,
             target_type_num,
+            sort as i32,
+            query.stars_from,
+            query.stars_to,
+            cursor.map(|c| c.key.clone()),
+            cursor.map(|c| c.id),
             limit,
-            offset
+            if cursor.is_some() { 0 } else { offset },
         )
         .fetch_all(&self.pool)
         .await
         .unwrap();
 
+        let mut next_cursor = None;
+
+        if rows.len() as i64 == limit {
+            if let Some(last) = rows.last() {
+                next_cursor = Some(models::ReviewCursor {
+                    sort,
+                    key: last.sort_key.clone(),
+                    id: last.id,
+                });
+            }
+        }
+
         for row in rows {
             reviews.push(models::Review {
                 id: Some(row.id),
@@ -2605,26 +4405,41 @@ impl Database {
                 flagged: row.flagged,
                 votes: self.get_review_votes(row.id).await,
                 star_rating: row.star_rating,
-                replies: self.get_review_replies(row.id).await,
+                replies: self.get_review_replies(row.id, 0).await,
                 parent_id: None,
             });
         }
 
-        reviews
+        (reviews, next_cursor)
     }
 
+    /// Gets the review stats of a entity. This is cached and invalidated when a review is changed
     pub async fn get_review_stats(
         &self,
         target_id: i64,
         target_type: models::TargetType,
     ) -> models::ReviewStats {
+        if let Some(stats) = self.review_stats_cache.get(&(target_id, target_type)) {
+            return (*stats).clone();
+        }
+
         let target_type_num = match target_type {
             models::TargetType::Bot => 0,
             models::TargetType::Server => 1,
         };
 
+        // An owner response is a reply to a root review made by a owner of the entity
         let stats = sqlx::query!(
-            "SELECT COUNT(*), AVG(star_rating) AS average_stars FROM reviews WHERE target_id = $1 AND target_type = $2 AND parent_id IS NULL",
+            "SELECT COUNT(*) AS total, AVG(star_rating) AS average_stars,
+            AVG(star_rating) FILTER (WHERE created_at > NOW() - interval '30 days') AS average_stars_30d,
+            AVG(star_rating) FILTER (WHERE created_at > NOW() - interval '90 days') AS average_stars_90d,
+            COUNT(*) FILTER (WHERE EXISTS (
+                SELECT 1 FROM reviews replies WHERE replies.parent_id = reviews.id AND replies.user_id IN (
+                    SELECT owner FROM bot_owner WHERE bot_id = $1 AND $2 = 0 
+                    UNION SELECT owner_id FROM servers WHERE guild_id = $1 AND $2 = 1
+                )
+            )) AS owner_responses
+            FROM reviews WHERE target_id = $1 AND target_type = $2 AND parent_id IS NULL",
             target_id,
             target_type_num
         )
@@ -2633,18 +4448,41 @@ impl Database {
 
         if stats.is_err() {
             error!("Error getting review stats: {}", stats.err().unwrap());
-            return models::ReviewStats {
-                total: 0,
-                average_stars: bigdecimal::BigDecimal::from_i64(0).unwrap(),
-            };
+            return models::ReviewStats::default();
         }
 
         let stats = stats.unwrap();
 
-        models::ReviewStats {
-            total: stats.count.unwrap_or_default(),
-            average_stars: stats.average_stars.unwrap_or_default(),
+        let buckets = sqlx::query!(
+            "SELECT FLOOR(star_rating)::integer AS bucket, COUNT(*) AS count FROM reviews 
+            WHERE target_id = $1 AND target_type = $2 AND parent_id IS NULL GROUP BY bucket",
+            target_id,
+            target_type_num
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+
+        // One bucket for every whole star from 0 to 10
+        let mut histogram = vec![0; 11];
+
+        for bucket in buckets {
+            let index = bucket.bucket.unwrap_or_default().clamp(0, 10) as usize;
+            histogram[index] += bucket.count.unwrap_or_default();
         }
+
+        let stats = models::ReviewStats {
+            total: stats.total.unwrap_or_default(),
+            average_stars: stats.average_stars.unwrap_or_default(),
+            histogram,
+            average_stars_30d: stats.average_stars_30d.unwrap_or_default(),
+            average_stars_90d: stats.average_stars_90d.unwrap_or_default(),
+            owner_responses: stats.owner_responses.unwrap_or_default(),
+        };
+
+        self.review_stats_cache.insert((target_id, target_type), Arc::new(stats.clone())).await;
+
+        stats
     }
 
     /// Get reviews for *a* user (not replies)
@@ -2679,7 +4517,7 @@ impl Database {
             flagged: row.flagged,
             votes: self.get_review_votes(row.id).await,
             star_rating: row.star_rating,
-            replies: self.get_review_replies(row.id).await,
+            replies: self.get_review_replies(row.id, 0).await,
             parent_id: None,
         });
     }
@@ -2714,20 +4552,45 @@ impl Database {
         .execute(&self.pool)
         .await?;
 
+        self.review_stats_cache.invalidate(&(target_id, target_type)).await;
+
         Ok(())
     }
 
-    pub async fn edit_review(&self, review: models::Review) -> Result<(), sqlx::Error> {
+    /// Edits a review, returns false if it does not exist
+    pub async fn edit_review(&self, review: models::Review) -> Result<bool, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        // Save the current revision before overwriting it
         sqlx::query!(
-            "UPDATE reviews SET star_rating = $1, review_text = $2 WHERE id = $3",
+            "INSERT INTO review_history (review_id, star_rating, review_text) 
+            SELECT id, star_rating, review_text FROM reviews WHERE id = $1",
+            review.id,
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let row = sqlx::query!(
+            "UPDATE reviews SET star_rating = $1, review_text = $2, flagged = flagged OR $4 
+            WHERE id = $3 RETURNING target_id, target_type",
             review.star_rating,
             review.review_text,
             review.id,
+            review.flagged,
         )
-        .execute(&self.pool)
+        .fetch_optional(&mut tx)
         .await?;
 
-        Ok(())
+        let row = match row {
+            Some(row) => row,
+            None => return Ok(false),
+        };
+
+        tx.commit().await?;
+
+        self.invalidate_review_stats(row.target_id, row.target_type).await;
+
+        Ok(true)
     }
 
     /// Get review votes for a review
@@ -2794,12 +4657,92 @@ impl Database {
         });
     }
 
-    pub async fn delete_review(&self, review_id: uuid::Uuid) -> Result<(), sqlx::Error> {
-        sqlx::query!("DELETE FROM reviews WHERE id = $1", review_id)
-            .execute(&self.pool)
+    /// Returns the amount of reviews with the same text made by the user on other entities
+    /// and the amount made by other users
+    pub async fn get_review_duplicates(&self, user_id: i64, target_id: i64, review_text: &str) -> (i64, i64) {
+        let row = sqlx::query!(
+            "SELECT COUNT(*) FILTER (WHERE user_id = $1 AND target_id != $2) AS own_duplicates,
+            COUNT(*) FILTER (WHERE user_id != $1) AS other_duplicates
+            FROM reviews WHERE LOWER(TRIM(review_text)) = LOWER(TRIM($3))",
+            user_id,
+            target_id,
+            review_text,
+        )
+        .fetch_one(&self.pool)
+        .await;
+
+        match row {
+            Ok(row) => (row.own_duplicates.unwrap_or_default(), row.other_duplicates.unwrap_or_default()),
+            Err(err) => {
+                error!("Error getting review duplicates: {}", err);
+                (0, 0)
+            }
+        }
+    }
+
+    /// Returns the prior revisions of a review, newest first
+    pub async fn get_review_history(&self, review_id: uuid::Uuid) -> Result<Vec<models::ReviewRevision>, sqlx::Error> {
+        let rows = sqlx::query!(
+            "SELECT star_rating, review_text, edited_at FROM review_history 
+            WHERE review_id = $1 ORDER BY edited_at DESC",
+            review_id,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut revisions = Vec::new();
+
+        for row in rows {
+            revisions.push(models::ReviewRevision {
+                star_rating: row.star_rating,
+                review_text: row.review_text,
+                edited_at: row.edited_at,
+            });
+        }
+
+        Ok(revisions)
+    }
+
+    /// Returns the target (entity id and type) a review was made on
+    pub async fn get_review_target(&self, review_id: uuid::Uuid) -> Option<(i64, models::TargetType)> {
+        let row = sqlx::query!(
+            "SELECT target_id, target_type FROM reviews WHERE id = $1",
+            review_id,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .ok()?;
+
+        let target_type = match row.target_type {
+            Some(1) => models::TargetType::Server,
+            _ => models::TargetType::Bot,
+        };
+
+        Some((row.target_id, target_type))
+    }
+
+    /// Deletes a review, returns false if it does not exist
+    pub async fn delete_review(&self, review_id: uuid::Uuid) -> Result<bool, sqlx::Error> {
+        let row = sqlx::query!("DELETE FROM reviews WHERE id = $1 RETURNING target_id, target_type", review_id)
+            .fetch_optional(&self.pool)
             .await?;
 
-        Ok(())
+        match row {
+            Some(row) => {
+                self.invalidate_review_stats(row.target_id, row.target_type).await;
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    async fn invalidate_review_stats(&self, target_id: i64, target_type: Option<i32>) {
+        let target_type = match target_type {
+            Some(1) => models::TargetType::Server,
+            _ => models::TargetType::Bot,
+        };
+
+        self.review_stats_cache.invalidate(&(target_id, target_type)).await;
     }
 
     pub async fn add_review_vote(
@@ -2864,95 +4807,186 @@ impl Database {
         return row.count.unwrap();
     }
 
-    pub async fn get_all_bots(&self) -> Vec<models::IndexBot> {
+    /// Browses bots with keyset pagination
+    pub async fn browse_bots(
+        &self,
+        query: &models::BrowseQuery,
+        cursor: Option<&models::BrowseCursor>,
+        limit: i64,
+    ) -> (Vec<models::IndexBot>, Option<models::BrowseCursor>) {
+        let sort = query.sort.unwrap_or_default();
+        let tags = split_list(&query.tags);
+        let features = split_list(&query.features);
+        let tag_mode_or = query.tag_mode.unwrap_or_default() == models::TagMode::Or;
+
+        let (state_a, state_b) = match query.state {
+            Some(state) => (state, state),
+            None => (models::State::Approved, models::State::Certified),
+        };
+
+        // Every page uses the time of the first page
+        let now = cursor.map_or_else(|| chrono::Utc::now().timestamp(), |c| c.now);
+
+        // Like reviews, every sort is mapped to a single descending sort_key
         let rows = sqlx::query!(
-            "SELECT bot_id, username_cached, avatar_cached, disc_cached, created_at,
-            guild_count, banner_card, description, votes, state, flags 
-            FROM bots ORDER BY votes DESC"
+            "SELECT bot_id, created_at, flags, description, banner_card, state, votes, guild_count, 
+            sort_key AS \"sort_key!\" FROM (
+                SELECT bots.bot_id, bots.created_at, bots.flags, bots.description, bots.banner_card, 
+                bots.state, bots.votes, bots.guild_count, CASE $1::integer
+                    WHEN 1 THEN COALESCE(bots.guild_count, 0)::numeric
+                    WHEN 2 THEN extract(epoch from bots.created_at)::numeric
+                    WHEN 3 THEN extract(epoch from bots.last_updated_at)::numeric
+                    WHEN 4 THEN ((
+                        SELECT COUNT(*) FROM bot_voters, unnest(bot_voters.timestamps) AS ts 
+                        WHERE bot_voters.bot_id = bots.bot_id AND ts > to_timestamp($10::bigint) - interval '7 days'
+                    ) / LN(2 + COALESCE(bots.votes, 0)))::numeric
+                    ELSE COALESCE(bots.votes, 0)::numeric
+                END AS sort_key FROM bots
+                WHERE (bots.state = $2 OR bots.state = $3)
+                AND ($4::text[] IS NULL OR (
+                    SELECT COUNT(DISTINCT bot_tags.tag) FROM bot_tags 
+                    WHERE bot_tags.bot_id = bots.bot_id AND bot_tags.tag = ANY($4)
+                ) >= (CASE WHEN $5 THEN 1 ELSE cardinality($4) END))
+                AND ($6::text[] IS NULL OR bots.features @> $6)
+            ) bots
+            WHERE ($7::numeric IS NULL OR (sort_key, bot_id) < ($7, $8::bigint))
+            ORDER BY sort_key DESC, bot_id DESC LIMIT $9",
+            sort as i32,
+            state_a as i32,
+            state_b as i32,
+            tags.as_deref(),
+            tag_mode_or,
+            features.as_deref(),
+            cursor.map(|c| c.key.clone()),
+            cursor.map(|c| c.id),
+            limit,
+            now,
         )
         .fetch_all(&self.pool)
-        .await;
+        .await
+        .unwrap();
 
-        if rows.is_err() {
-            return Vec::new();
-        }
+        let mut next_cursor = None;
 
-        let rows = rows.unwrap();
+        if rows.len() as i64 == limit {
+            if let Some(last) = rows.last() {
+                next_cursor = Some(models::BrowseCursor {
+                    sort,
+                    key: last.sort_key.clone(),
+                    id: last.bot_id,
+                    now,
+                });
+            }
+        }
 
-        let mut list = Vec::new();
+        let mut bots = Vec::new();
 
         for row in rows {
-            let mut user = models::User {
-                id: row.bot_id.to_string(),
-                username: row.username_cached,
-                disc: row.disc_cached, // This is unknown
-                avatar: row.avatar_cached,           // This is unknown
-                bot: true,
-                status: models::Status::Unknown,    
-            };
-
-            user.avatar = if user.avatar.is_empty() {
-                "https://api.fateslist.xyz/static/botlisticon.webp".to_string()
-            } else {
-                user.avatar
-            };
-
-            list.push(models::IndexBot {
-                user,
-                created_at: row.created_at,
-                state: models::State::try_from(row.state).unwrap_or(models::State::Banned),
+            bots.push(models::IndexBot {
+                guild_count: row.guild_count.unwrap_or(0),
                 description: row.description,
                 banner: row.banner_card.unwrap_or_else(|| {
                     "https://api.fateslist.xyz/static/assets/prod/banner.webp".to_string()
                 }),
-                guild_count: row.guild_count.unwrap_or_default(),
-                votes: row.votes.unwrap_or_default(),
+                state: models::State::try_from(row.state).unwrap_or(models::State::Approved),
+                votes: row.votes.unwrap_or(0),
                 flags: row.flags,
+                user: self.get_user(row.bot_id).await,
+                review_stats: Some(self.get_review_stats(row.bot_id, models::TargetType::Bot).await),
+                search_meta: None,
+                created_at: row.created_at,
             });
         }
 
-        list
+        (bots, next_cursor)
     }
 
-    pub async fn get_all_servers(&self) -> Vec<models::IndexBot> {
+    /// Browses servers with keyset pagination. Servers do not have features and do not 
+    /// track when they were last updated so ``RecentlyUpdated`` is the same as ``Newest``
+    pub async fn browse_servers(
+        &self,
+        query: &models::BrowseQuery,
+        cursor: Option<&models::BrowseCursor>,
+        limit: i64,
+    ) -> (Vec<models::IndexBot>, Option<models::BrowseCursor>) {
+        let sort = query.sort.unwrap_or_default();
+        let tags = split_list(&query.tags);
+        let tag_mode_or = query.tag_mode.unwrap_or_default() == models::TagMode::Or;
+
+        let (state_a, state_b) = match query.state {
+            Some(state) => (state, state),
+            None => (models::State::Approved, models::State::Certified),
+        };
+
+        // Every page uses the time of the first page
+        let now = cursor.map_or_else(|| chrono::Utc::now().timestamp(), |c| c.now);
+
         let rows = sqlx::query!(
-            "SELECT guild_id, name_cached, guild_count, banner_card, 
-            created_at, description, votes, state, flags FROM servers"
+            "SELECT guild_id, created_at, flags, description, banner_card, state, votes, guild_count, 
+            sort_key AS \"sort_key!\" FROM (
+                SELECT servers.guild_id, servers.created_at, servers.flags, servers.description, 
+                servers.banner_card, servers.state, servers.votes, servers.guild_count, CASE $1::integer
+                    WHEN 1 THEN COALESCE(servers.guild_count, 0)::numeric
+                    WHEN 2 THEN extract(epoch from servers.created_at)::numeric
+                    WHEN 3 THEN extract(epoch from servers.created_at)::numeric
+                    WHEN 4 THEN ((
+                        SELECT COUNT(*) FROM server_voters, unnest(server_voters.timestamps) AS ts 
+                        WHERE server_voters.guild_id = servers.guild_id AND ts > to_timestamp($9::bigint) - interval '7 days'
+                    ) / LN(2 + COALESCE(servers.votes, 0)))::numeric
+                    ELSE COALESCE(servers.votes, 0)::numeric
+                END AS sort_key FROM servers
+                WHERE (servers.state = $2 OR servers.state = $3)
+                AND ($4::text[] IS NULL OR (CASE WHEN $5 THEN servers.tags && $4 ELSE servers.tags @> $4 END))
+            ) servers
+            WHERE ($6::numeric IS NULL OR (sort_key, guild_id) < ($6, $7::bigint))
+            ORDER BY sort_key DESC, guild_id DESC LIMIT $8",
+            sort as i32,
+            state_a as i32,
+            state_b as i32,
+            tags.as_deref(),
+            tag_mode_or,
+            cursor.map(|c| c.key.clone()),
+            cursor.map(|c| c.id),
+            limit,
+            now,
         )
         .fetch_all(&self.pool)
-        .await;
+        .await
+        .unwrap();
 
-        if rows.is_err() {
-            return Vec::new();
-        }
+        let mut next_cursor = None;
 
-        let rows = rows.unwrap();
+        if rows.len() as i64 == limit {
+            if let Some(last) = rows.last() {
+                next_cursor = Some(models::BrowseCursor {
+                    sort,
+                    key: last.sort_key.clone(),
+                    id: last.guild_id,
+                    now,
+                });
+            }
+        }
 
-        let mut list = Vec::new();
+        let mut servers = Vec::new();
 
         for row in rows {
-            list.push(models::IndexBot {
-                user: models::User {
-                    id: row.guild_id.to_string(),
-                    username: row.name_cached,
-                    disc: "0000".to_string(), // This is unknown
-                    avatar: "https://api.fateslist.xyz/static/botlisticon.webp".to_string(), // This is unknown
-                    bot: true,
-                    status: models::Status::Unknown,
-                },
-                created_at: row.created_at,
-                state: models::State::try_from(row.state).unwrap_or(models::State::Banned),
+            servers.push(models::IndexBot {
+                guild_count: row.guild_count.unwrap_or(0),
                 description: row.description,
                 banner: row.banner_card.unwrap_or_else(|| {
                     "https://api.fateslist.xyz/static/assets/prod/banner.webp".to_string()
                 }),
-                guild_count: row.guild_count.unwrap_or_default(),
-                votes: row.votes.unwrap_or_default(),
                 flags: row.flags,
+                state: models::State::try_from(row.state).unwrap_or(models::State::Approved),
+                votes: row.votes.unwrap_or(0),
+                user: self.get_server_user(row.guild_id).await,
+                review_stats: Some(self.get_review_stats(row.guild_id, models::TargetType::Server).await),
+                search_meta: None,
+                created_at: row.created_at,
             });
         }
 
-        list
+        (servers, next_cursor)
     }
 
     // Commands
@@ -2980,7 +5014,7 @@ impl Database {
                     vote_locked = $8, nsfw = $9 WHERE bot_id = $10 AND 
                     cmd_type = $11 AND name = $12",
                     command.description,
-                    &command.args,
+                    json!(command.args),
                     &command.examples,
                     command.premium_only,
                     &command.notes,
@@ -3007,7 +5041,7 @@ impl Database {
                             command.cmd_type as i32,
                             command.name,
                             command.description,
-                            &command.args,
+                            json!(command.args),
                             &command.examples,
                             command.premium_only,
                             &command.notes,
@@ -3029,6 +5063,157 @@ impl Database {
         Ok(())
     }
 
+    /// Makes the commands of a bot match ``commands`` exactly. Commands are matched by 
+    /// ``(cmd_type, name)``, all changes are made in a single transaction
+    pub async fn sync_commands(
+        &self,
+        bot_id: i64,
+        commands: &[models::BotCommand],
+    ) -> Result<models::CommandSyncSummary, sqlx::Error> {
+        let mut summary = models::CommandSyncSummary::default();
+
+        let mut tx = self.pool.begin().await?;
+
+        let existing_rows = sqlx::query!(
+            "SELECT id, cmd_type, description, args, examples, 
+            premium_only, notes, doc_link, groups, name, 
+            vote_locked, nsfw FROM bot_commands WHERE bot_id = $1 FOR UPDATE",
+            bot_id
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        let mut existing = IndexMap::new();
+        let mut stale = Vec::new();
+
+        for row in existing_rows {
+            let key = (row.cmd_type, row.name.clone());
+            // Older clients could create duplicates, only the first one is kept
+            if existing.contains_key(&key) {
+                stale.push(row.id);
+                summary.deleted.push(models::CommandSyncItem {
+                    cmd_type: models::CommandType::try_from(row.cmd_type).unwrap_or_default(),
+                    name: row.name,
+                });
+            } else {
+                existing.insert(key, row);
+            }
+        }
+
+        for command in commands {
+            let key = (command.cmd_type as i32, command.name.clone());
+
+            let item = models::CommandSyncItem {
+                cmd_type: command.cmd_type,
+                name: command.name.clone(),
+            };
+
+            match existing.swap_remove(&key) {
+                Some(row) => {
+                    let unchanged = row.description == command.description
+                        && serde_json::from_value::<Vec<models::CommandArg>>(row.args.clone()).unwrap_or_default() == command.args
+                        && row.examples == command.examples
+                        && row.premium_only == command.premium_only
+                        && row.notes == command.notes
+                        && row.doc_link == command.doc_link
+                        && row.groups == command.groups
+                        && row.vote_locked == command.vote_locked
+                        && row.nsfw.unwrap_or(false) == command.nsfw;
+
+                    if unchanged {
+                        summary.unchanged += 1;
+                        continue;
+                    }
+
+                    sqlx::query!(
+                        "UPDATE bot_commands SET description = $1, args = $2, examples = $3, 
+                        premium_only = $4, notes = $5, doc_link = $6, groups = $7, 
+                        vote_locked = $8, nsfw = $9 WHERE id = $10",
+                        command.description,
+                        json!(command.args),
+                        &command.examples,
+                        command.premium_only,
+                        &command.notes,
+                        command.doc_link,
+                        &command.groups,
+                        command.vote_locked,
+                        command.nsfw,
+                        row.id,
+                    )
+                    .execute(&mut tx)
+                    .await?;
+
+                    summary.updated.push(item);
+                },
+                None => {
+                    sqlx::query!(
+                        "INSERT INTO bot_commands (bot_id, cmd_type, name, 
+                        description, args, examples, premium_only, notes, doc_link,
+                        groups, vote_locked, nsfw) 
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+                        bot_id,
+                        command.cmd_type as i32,
+                        command.name,
+                        command.description,
+                        json!(command.args),
+                        &command.examples,
+                        command.premium_only,
+                        &command.notes,
+                        command.doc_link,
+                        &command.groups,
+                        command.vote_locked,
+                        command.nsfw,
+                    ) 
+                    .execute(&mut tx)
+                    .await?;
+
+                    summary.added.push(item);
+                }
+            }
+        }
+
+        // Anything left over is no longer in the list
+        for (_, row) in existing {
+            stale.push(row.id);
+            summary.deleted.push(models::CommandSyncItem {
+                cmd_type: models::CommandType::try_from(row.cmd_type).unwrap_or_default(),
+                name: row.name,
+            });
+        }
+
+        sqlx::query!("DELETE FROM bot_commands WHERE id = ANY($1)", &stale)
+            .execute(&mut tx)
+            .await?;
+
+        tx.commit().await?;
+
+        // Changes are only announced once they are saved
+        let changes = summary.added.iter().chain(summary.updated.iter())
+            .map(|item| (models::EventName::CommandAdd, item))
+            .chain(summary.deleted.iter().map(|item| (models::EventName::CommandDelete, item)));
+
+        for (event_name, item) in changes {
+            self.ws_event(models::Event {
+                m: models::EventMeta {
+                    e: event_name,
+                    eid: uuid::Uuid::new_v4().to_hyphenated().to_string(),
+                },
+                ctx: models::EventContext {
+                    target: bot_id.to_string(),
+                    target_type: models::TargetType::Bot,
+                    user: None,
+                    ts: chrono::Utc::now().timestamp(),
+                },
+                props: models::CommandEventProp {
+                    cmd_type: item.cmd_type,
+                    name: item.name.clone(),
+                },
+            }).await;
+        }
+
+        Ok(summary)
+    }
+
     pub async fn delete_all_commands(&self, id: i64) {
         sqlx::query!("DELETE FROM bot_commands WHERE bot_id = $1", id,)
             .execute(&self.pool)
@@ -3689,7 +5874,11 @@ impl Database {
     }
 
     pub async fn test_notifs(&self, id: i64) {
-        // Test API call
+        self.notify_user(id, "Test notification").await;
+    }
+
+    /// Sends a push notification to all devices of a user
+    pub async fn notify_user(&self, id: i64, title: &str) {
         let devices = sqlx::query!(
             "SELECT endpoint, p256dh, auth FROM push_notifications WHERE user_id = $1",
             id
@@ -3712,18 +5901,175 @@ impl Database {
                 p256dh: device.p256dh,
                 auth: device.auth,
                 data: serde_json::to_string(&json!({
-                    "title": "Test notification",
+                    "title": title,
                     "icon": "https://api.fateslist.xyz/static/botlisticon.webp"
                 })).unwrap(),
             })
             .send()
             .await;
 
-            if res.is_err() {
-                error!("Failed to send test notification to user {}", id);
+            match res {
+                Ok(res) => debug!("{}", res.status()),
+                Err(_) => error!("Failed to send notification to user {}", id),
+            }
+        }
+    }
+
+    /// Removes deleted, banned and denied bots from packs and notifies the pack owners. Packs
+    /// left with less than 2 bots are unlisted
+    pub async fn prune_packs(&self) {
+        let rows = sqlx::query!(
+            "DELETE FROM bot_pack_bots WHERE NOT EXISTS (
+                SELECT 1 FROM bots WHERE bots.bot_id = bot_pack_bots.bot_id 
+                AND bots.state != $1 AND bots.state != $2
+            ) RETURNING pack_id, bot_id",
+            models::State::Banned as i32,
+            models::State::Denied as i32,
+        )
+        .fetch_all(&self.pool)
+        .await;
+
+        let rows = match rows {
+            Ok(rows) => rows,
+            Err(err) => {
+                error!("Could not prune packs: {}", err);
+                return;
+            }
+        };
+
+        let mut removed: IndexMap<uuid::Uuid, i64> = IndexMap::new();
+
+        for row in rows {
+            *removed.entry(row.pack_id).or_insert(0) += 1;
+        }
+
+        for (pack_id, count) in removed {
+            let pack = sqlx::query!("SELECT owner, name FROM bot_packs WHERE id = $1", pack_id)
+                .fetch_optional(&self.pool)
+                .await
+                .unwrap_or_default();
+
+            if let Some(pack) = pack {
+                debug!("Pruned {} bots from pack {}", count, pack_id);
+
+                // Packs need at least 2 bots, smaller ones are unlisted until the owner adds more
+                let unlisted = sqlx::query!(
+                    "UPDATE bot_packs SET visibility = $2 WHERE id = $1 
+                    AND (SELECT COUNT(*) FROM bot_pack_bots WHERE pack_id = $1) < 2",
+                    pack_id,
+                    models::PackVisibility::Unlisted as i32,
+                )
+                .execute(&self.pool)
+                .await
+                .map_or(false, |res| res.rows_affected() > 0);
+
+                let mut message = format!(
+                    "{} bot(s) were removed from your pack {} as they are no longer on the list", 
+                    count, 
+                    pack.name
+                );
+
+                if unlisted {
+                    message.push_str(". It has been unlisted as it now has less than 2 bots");
+                }
+
+                self.notify_user(pack.owner, &message).await;
             }
+        }
+    }
+}
+
+/// Facet counts aggregated by postgres (``json_object_agg``), in order of the count
+fn facet_counts(counts: Option<serde_json::Value>) -> IndexMap<String, i64> {
+    counts
+        .and_then(|counts| serde_json::from_value(counts).ok())
+        .unwrap_or_default()
+}
+
+/// The approximate count lags behind a bit so only large differences count
+fn off_approx(guild_count: i64, approx_count: i64) -> bool {
+    approx_count > 0 && (guild_count - approx_count).abs() > 100.max(approx_count / 4)
+}
 
-            debug!("{}", res.unwrap().status());
+/// Checks a stats post against the last post and the approximate guild count from Discord. 
+/// Posts that jump suddenly *and* are far from the approximate count are rejected, posts that 
+/// only do one of these are stored with the returned reason
+fn detect_stats_anomaly(
+    guild_count: i64,
+    approx_count: Option<i64>,
+    last: Option<(i64, chrono::DateTime<chrono::Utc>)>,
+) -> Result<Option<String>, String> {
+    let off_approx = approx_count.filter(|approx_count| off_approx(guild_count, *approx_count));
+
+    let jump = last.and_then(|(last_count, last_post)| {
+        let delta = guild_count - last_count;
+        let hours = (chrono::Utc::now() - last_post).num_hours().max(1);
+
+        // Growing or shrinking by half within a day
+        if hours <= 24 && delta.abs() > 100.max(last_count / 2) {
+            Some(format!("Guild count changed by {} in {} hours", delta, hours))
+        } else {
+            None
         }
+    });
+
+    match (jump, off_approx) {
+        (Some(jump), Some(approx_count)) => Err(format!("{} while Discord reports about {} guilds", jump, approx_count)),
+        (Some(jump), None) => Ok(Some(jump)),
+        (None, Some(approx_count)) => Ok(Some(format!("Guild count is far from the approximate guild count of {}", approx_count))),
+        (None, None) => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hours_ago(hours: i64) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now() - chrono::Duration::hours(hours)
+    }
+
+    #[test]
+    fn stats_without_history() {
+        assert_eq!(detect_stats_anomaly(1000, None, None), Ok(None));
+        assert_eq!(detect_stats_anomaly(1000, Some(1000), None), Ok(None));
+    }
+
+    #[test]
+    fn stats_jump() {
+        let jump = detect_stats_anomaly(2000, None, Some((1000, hours_ago(1))));
+        assert!(matches!(jump, Ok(Some(reason)) if reason.starts_with("Guild count changed by 1000")));
+
+        // Shrinking counts too
+        let jump = detect_stats_anomaly(400, None, Some((1000, hours_ago(1))));
+        assert!(matches!(jump, Ok(Some(_))));
+
+        // Normal growth, small bots and old posts are fine
+        assert_eq!(detect_stats_anomaly(1400, None, Some((1000, hours_ago(1)))), Ok(None));
+        assert_eq!(detect_stats_anomaly(150, None, Some((60, hours_ago(1)))), Ok(None));
+        assert_eq!(detect_stats_anomaly(2000, None, Some((1000, hours_ago(48)))), Ok(None));
+    }
+
+    #[test]
+    fn stats_off_approx() {
+        let off = detect_stats_anomaly(2000, Some(1000), None);
+        assert!(matches!(off, Ok(Some(reason)) if reason.ends_with("of 1000")));
+
+        // The approximate count lags behind a bit
+        assert_eq!(detect_stats_anomaly(1200, Some(1000), None), Ok(None));
+        assert_eq!(detect_stats_anomaly(150, Some(60), None), Ok(None));
+
+        // No approximate count is known
+        assert_eq!(detect_stats_anomaly(2000, Some(0), None), Ok(None));
+    }
+
+    #[test]
+    fn stats_jump_and_off_approx() {
+        let rejected = detect_stats_anomaly(5000, Some(1000), Some((1000, hours_ago(1))));
+        assert!(matches!(rejected, Err(reason) if reason.ends_with("about 1000 guilds")));
+
+        // A jump Discord agrees with is stored but not rejected
+        let jump = detect_stats_anomaly(2000, Some(2000), Some((1000, hours_ago(1))));
+        assert!(matches!(jump, Ok(Some(_))));
     }
 }
@@ -1,3 +1,4 @@
+use crate::converters;
 use crate::models;
 use docser;
 use bigdecimal::FromPrimitive;
@@ -289,6 +290,21 @@ A default API Response will be of the below format:
     context: "" | null
 }
 ```
+
+## Rate Limits
+
+Requests are rate limited per token and route, or per IP and route for requests without a 
+token. Requests with a token are also limited per IP to the limit of routes without their own 
+limit, the headers are for whichever limit is closer to being reached. Every response has the 
+below headers:
+
+- ``X-RateLimit-Limit``: The number of requests allowed in the current window
+- ``X-RateLimit-Remaining``: The number of requests left in the current window
+- ``X-RateLimit-Reset``: When the current window resets as a unix timestamp (in seconds)
+
+Going over the limit returns a ``429 Too Many Requests`` with a ``Retry-After`` header 
+holding the number of seconds to wait. Routes such as [Post Stats](https://lynx.fateslist.xyz/docs/endpoints/bot-actions#post-stats) 
+have stricter limits than the rest of the API
 "#;
 
     let index_bots = vec![models::IndexBot::default()];
@@ -297,6 +313,13 @@ A default API Response will be of the below format:
 
     let features = vec![models::Feature::default()];        
 
+    let browse_cursor = converters::encode_browse_cursor(&models::BrowseCursor {
+        sort: models::BrowseSort::Votes,
+        key: bigdecimal::BigDecimal::from(123),
+        id: 0,
+        now: 1_650_000_000,
+    });
+
     // TODO: For each route, add doc system
     doc(
         BASIC_API,
@@ -311,8 +334,26 @@ A default API Response will be of the below format:
                         path_params: "",
                         query_params: &body(QUERY_PARAMS, &models::IndexQuery {
                             target_type: models::TargetType::Server,
+                            user_id: Some(0),
+                            limit: Some(12),
+                            nsfw: Some(false),
+                            exclude_voted: Some(false),
                         }),
-                        description: "Returns the index for bots and servers",
+                        description: r#"
+Returns the index for bots and servers.
+
+If ``user_id`` is set, the users token must be given in ``Authorization``. In this case, 
+``recommended`` will contain bots similar to the ones the user has voted for. 
+This only applies to the bot index, ``recommended`` is otherwise always empty.
+
+``limit`` is the size of every section and defaults to 12. It must be between 1 and 50.
+
+``nsfw`` controls whether NSFW bots and servers are shown. If not set, this defaults to the 
+``show_nsfw`` preference on the users profile when ``user_id`` is set and ``true`` otherwise.
+
+If ``exclude_voted`` is set to ``true``, bots and servers the user has voted for are left out. 
+This requires ``user_id`` to be set.
+"#,
                         request_body: "",
                         response_body: &body(RESP_BODY, &models::Index {
                             top_voted: index_bots.clone(),
@@ -320,6 +361,34 @@ A default API Response will be of the below format:
                             new: index_bots.clone(),
                             tags: tags.clone(),
                             features: features.clone(),
+                            recommended: index_bots.clone(),
+                            trending: models::Trending {
+                                day: index_bots.clone(),
+                                week: index_bots.clone(),
+                            },
+                        }),
+                        auth_types: vec![models::RouteAuthType::User]
+                    },
+
+                    models::Route {
+                        title: "Trending",
+                        method: "GET",
+                        path: "/trending",
+                        path_params: "",
+                        query_params: &body(QUERY_PARAMS, &models::TrendingQuery {
+                            target_type: models::TargetType::Bot,
+                        }),
+                        description: r#"
+Returns the bots or servers getting the most votes (and views) over the last day 
+and the last week. Scores are divided by the log of the all time votes so smaller 
+bots and servers on the rise are not drowned out by the largest ones.
+
+This is the same as ``trending`` in the index and is cached along with it.
+"#,
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::Trending {
+                            day: index_bots.clone(),
+                            week: index_bots.clone(),
                         }),
                         auth_types: vec![]
                     },
@@ -379,11 +448,49 @@ A default API Response will be of the below format:
                             q: "mew".to_string(),
                             gc_from: 1,
                             gc_to: -1,
+                            page: Some(1),
+                            per_page: Some(12),
+                            tags: Some("music|moderation".to_string()),
+                            tag_mode: Some(models::TagMode::Or),
+                            features: Some("open_source".to_string()),
+                            library: Some("discord.py".to_string()),
+                            page_style: None,
+                            certified_only: Some(false),
+                            nsfw: Some(false),
+                            created_after: None,
                         }),
                         description: r#"
 Searches the list based on a query named ``q``. 
         
-Using -1 for ``gc_to`` will disable ``gc_to`` field"#,
+Using -1 for ``gc_to`` will disable ``gc_to`` field. ``gc_from`` and ``gc_to`` apply to both
+bots and servers.
+
+Bots and servers are ranked by relevance. Names are weighted highest followed by the short 
+description, the long description and finally the owners. Every word in ``q`` is prefix 
+matched and small typos in names are tolerated.
+
+Bots and servers have a ``search_meta`` containing the ``rank`` of the result and a ``snippet`` 
+of the description where matches are wrapped in ``<mark>`` tags. The rest of the snippet is 
+HTML escaped. ``search_meta`` is null outside of search results.
+
+``page`` defaults to 1 and ``per_page`` defaults to 12 (maximum 50). These apply to every 
+section of the results separately.
+
+The following optional filters are also supported:
+
+- ``tags``: Tags seperated by ``|``. ``tag_mode`` is a [TagMode](https://lynx.fateslist.xyz/docs/endpoints/enums#tagmode) 
+and controls whether results must have all of the tags (the default) or any of them
+- ``features``: Feature ids seperated by ``|``, results must have all of them (bots only)
+- ``library``: The library of the bot (bots only, case insensitive)
+- ``page_style``: A [PageStyle](https://lynx.fateslist.xyz/docs/endpoints/enums#pagestyle) (bots only)
+- ``certified_only``: Only return certified bots and servers
+- ``nsfw``: ``true`` for only NSFW results, ``false`` to exclude NSFW results. Omit for both
+- ``created_after``: Only return results created after this time (RFC 3339)
+
+``facets`` contains the counts of every tag, feature, library and page style among all bots
+matching the search (not just the current page) so filter sidebars can be rendered. ``total``
+is the total amount of matching bots. ``server_facets`` contains the same for servers (servers
+do not have features, libraries or page styles)."#,
                         request_body: "",
                         response_body: &body(RESP_BODY, &models::Search {
                             bots: vec![models::IndexBot::default()],
@@ -394,10 +501,94 @@ Using -1 for ``gc_to`` will disable ``gc_to`` field"#,
                                 bots: vec![models::Tag::default()],
                                 servers: vec![models::Tag::default()]
                             },
+                            facets: models::SearchFacets::default(),
+                            server_facets: models::SearchFacets::default(),
+                        }),
+                        auth_types: vec![]
+                    },
+
+                    models::Route {
+                        title: "Search Profiles",
+                        method: "GET",
+                        path: "/search/profiles?q={query}",
+                        path_params: "",
+                        query_params: &body(QUERY_PARAMS, &models::PagedSearchQuery {
+                            q: "mew".to_string(),
+                            page: Some(1),
+                            per_page: Some(12),
+                        }),
+                        description: r#"
+Searches profiles by username or by the bots they own. Only users owning at least one approved 
+or certified bot are returned. Results are ranked by relevance.
+
+``page`` defaults to 1 and ``per_page`` defaults to 12 (maximum 50). ``total`` is the total 
+amount of matching profiles and ``from`` is the index of the first profile of the page."#,
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::ProfileSearch {
+                            profiles: vec![models::SearchProfile::default()],
+                            total: 1,
+                            per_page: 12,
+                            from: 0,
+                        }),
+                        auth_types: vec![]
+                    },
+
+                    models::Route {
+                        title: "Search Packs",
+                        method: "GET",
+                        path: "/search/packs?q={query}",
+                        path_params: "",
+                        query_params: &body(QUERY_PARAMS, &models::PagedSearchQuery {
+                            q: "mew".to_string(),
+                            page: Some(1),
+                            per_page: Some(12),
+                        }),
+                        description: r#"
+Searches packs by name, description, owner or the bots in them. Only packs containing at least
+one approved or certified bot are returned. Packs matching on their own name or description 
+are ranked above packs that only match because of a bot in them.
+
+``page`` defaults to 1 and ``per_page`` defaults to 12 (maximum 50). ``total`` is the total 
+amount of matching packs and ``from`` is the index of the first pack of the page."#,
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::PackSearch {
+                            packs: vec![models::BotPack::default()],
+                            total: 1,
+                            per_page: 12,
+                            from: 0,
                         }),
                         auth_types: vec![]
                     },
 
+                    models::Route {
+                        title: "Search Suggest",
+                        method: "GET",
+                        path: "/search/suggest?q={query}",
+                        path_params: "",
+                        query_params: &body(QUERY_PARAMS, &models::SuggestQuery {
+                            q: "mew".to_string(),
+                            limit: Some(8),
+                        }),
+                        description: r#"
+Returns search suggestions (names and vanities starting with ``q``) for bots, servers, packs
+and tags. Suggestions are sorted by popularity.
+
+This is meant for search boxes and is much faster than ``Search List``. Suggestions are 
+served from memory and refreshed every 5 minutes so new bots may take a while to show up.
+
+``limit`` defaults to 8 and can be at most 25.
+
+``suggestion_type`` is a [SuggestionType](https://lynx.fateslist.xyz/docs/endpoints/enums#suggestiontype)"#,
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &vec![models::Suggestion {
+                            id: "0".to_string(),
+                            name: "Mewbot".to_string(),
+                            vanity: Some("mewbot".to_string()),
+                            suggestion_type: models::SuggestionType::Bot,
+                        }]),
+                        auth_types: vec![]
+                    },
+
                     models::Route {
                         title: "Search Tags",
                         method: "GET",
@@ -406,7 +597,7 @@ Using -1 for ``gc_to`` will disable ``gc_to`` field"#,
                         query_params: &body(QUERY_PARAMS, &models::SearchTagQuery {
                             q: "mew".to_string(),
                         }),
-                        description: "Searches the list based on a tag named ``q``.",
+                        description: "Searches the list based on a tag named ``q``. Both approved and certified bots and servers are returned.",
                         request_body: "",
                         response_body: &body(RESP_BODY, &models::Search {
                             bots: vec![models::IndexBot::default()],
@@ -417,6 +608,8 @@ Using -1 for ``gc_to`` will disable ``gc_to`` field"#,
                                 bots: vec![models::Tag::default()],
                                 servers: vec![models::Tag::default()]
                             },
+                            facets: models::SearchFacets::default(),
+                            server_facets: models::SearchFacets::default(),
                         }),
                         auth_types: vec![]
                     },
@@ -434,6 +627,8 @@ Using -1 for ``gc_to`` will disable ``gc_to`` field"#,
                             certified: Vec::new(),
                             tags: tags.clone(),
                             features: features.clone(),
+                            recommended: Vec::new(),
+                            trending: models::Trending::default(),
                         }),
                         description: r#"
 Returns a mini-index which is basically a Index but with only ``tags``
@@ -643,11 +838,27 @@ token ever gets leaked.
                             shard_count: Some(48484),
                             shards: Some(vec![149, 22020]),
                             user_count: Some(39393),
+                            shard_guilds: None,
                         }),
                         response_body: &body(RESP_BODY, &models::APIResponse::default()),
                         description: r#"
 Post stats to the list
 
+- ``shard_guilds`` is optional and is the guild count of each shard (the index being the shard id). 
+It must add up to ``guild_count`` and have at most ``shard_count`` entries
+- Every post is kept for a year, see [Get Stats History](#get-stats-history)
+- Posts are compared to the last post and the approximate guild count Discord reports. Posts 
+that grow or shrink by more than half within a day *and* are far from the approximate guild 
+count are rejected with ``Anomaly``. Posts that only do one of these are accepted but marked 
+in the history
+- If the application info of the bot cannot be fetched right now, the post is accepted with a 
+``202 Accepted`` status and checked against the approximate guild count later. Posts that turn 
+out to be far from it are marked in the history and the guild count is set to the approximate 
+guild count
+- Approximate guild counts are only known when the list uses japi.rest for application info. 
+Otherwise all checks against the approximate guild count are skipped and only sudden jumps 
+are marked
+
 Example:
 ```py
 import requests
@@ -687,6 +898,10 @@ This endpoint handles both bot IDs and client IDs
 - ``long_description/css`` is sanitized with ammonia by default, use `long_description_raw` if you want the unsanitized version
 - All responses are cached for a short period of time. There is *no* way to opt out at this time
 - Some fields have been renamed or removed from API v2 (such as ``promos`` which may be readded at a later date)
+- ``events`` only has active events. ``in_maintenance`` is set (and ``maintenance`` is the event) while 
+a ``Maintenance`` [event](./bot-events#add-bot-event) is active so clients can show a banner
+- If the active maintenance event pauses invites, requests with the Frostpaw-Invite header fail with 
+``InvitesPaused``
 
 This API returns some empty fields such as ``webhook``, ``webhook_secret``, ``api_token`` and more. 
 This is to allow reuse of the Bot struct in Get Bot Settings which *does* contain this sensitive data. 
@@ -731,12 +946,18 @@ Staff members *must* instead use Lynx."#,
                         method: "GET",
                         path: "/random-bot",
                         path_params: "",
-                        query_params: "",
+                        query_params: &body(QUERY_PARAMS, &models::RandomQuery {
+                            nsfw: Some(false),
+                        }),
                         request_body: "",
                         response_body: &body(RESP_BODY, &models::IndexBot::default()),
                         description: r#"
 Fetches a random bot on the list
 
+NSFW bots are only returned if ``nsfw`` is set to ``true``
+
+Returns a 404 if no bots match
+
 Example:
 ```py
 import requests
@@ -751,6 +972,118 @@ def random_bot():
 ```"#,
                         auth_types: vec![]
                     },
+
+                    models::Route {
+                        title: "Browse Bots",
+                        method: "GET",
+                        path: "/bots",
+                        path_params: "",
+                        query_params: &body(QUERY_PARAMS, &models::BrowseQuery {
+                            sort: Some(models::BrowseSort::Trending),
+                            state: Some(models::State::Certified),
+                            tags: Some("music|fun".to_string()),
+                            tag_mode: Some(models::TagMode::And),
+                            features: Some("custom_prefix".to_string()),
+                            per_page: Some(25),
+                            cursor: None,
+                        }),
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::Browse {
+                            results: vec![models::IndexBot::default()],
+                            next_cursor: Some(browse_cursor.clone()),
+                        }),
+                        description: r#"
+Browses all bots on the list, page by page.
+
+``sort`` is a [BrowseSort](https://lynx.fateslist.xyz/docs/endpoints/enums#browsesort) and 
+defaults to ``Votes``. ``Trending`` uses votes over the last 7 days relative to all time votes.
+
+``features`` is a ``|`` separated list of features that all must be present.
+
+``state`` defaults to approved and certified bots and can only be ``Approved`` or ``Certified``. ``tags`` is a ``|`` separated list of 
+tags and ``tag_mode`` (a [TagMode](https://lynx.fateslist.xyz/docs/endpoints/enums#tagmode)) 
+controls whether all or any of them must match.
+
+``per_page`` defaults to 25 and must be between 1 and 100.
+
+``next_cursor`` will be set if there may be more bots. Pass it back as ``cursor`` (with the 
+same ``sort``) to get the next page. Cursors are opaque and should not be parsed by clients."#,
+                        auth_types: vec![]
+                    },
+
+                    models::Route {
+                        title: "Get Similar Bots",
+                        method: "GET",
+                        path: "/bots/{id}/similar",
+                        path_params: &body(PATH_PARAMS, &models::FetchBotPath::default()),
+                        query_params: "",
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &vec![models::IndexBot::default()]),
+                        description: r#"
+Returns up to 12 bots similar to this one, best match first.
+
+Bots are considered similar when the same users vote for both of them, 
+when they share tags and when they share features. Similar bots are 
+recomputed every few hours so newly added bots may not have any yet.
+"#,
+                        auth_types: vec![]
+                    },
+
+                    models::Route {
+                        title: "Get Stats History",
+                        method: "GET",
+                        path: "/bots/{id}/stats/history",
+                        path_params: &body(PATH_PARAMS, &models::FetchBotPath::default()),
+                        query_params: &body(QUERY_PARAMS, &models::StatsHistoryQuery {
+                            range: Some(models::StatsRange::Week),
+                        }),
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::StatsHistory {
+                            range: models::StatsRange::Week,
+                            points: vec![models::StatsPoint::default()],
+                            growth: 120,
+                        }),
+                        description: r#"
+Returns the posted stats of a bot over the last day, week (the default), month or year for 
+growth charts. ``range`` is a [StatsRange](https://lynx.fateslist.xyz/docs/endpoints/enums#statsrange).
+
+There is one point per hour (day and week) or per day (month and year), which is the last 
+post in it, oldest first. ``anomaly`` is set on points that looked suspicious when posted.
+"#,
+                        auth_types: vec![]
+                    },
+
+                    models::Route {
+                        title: "Get Bot Uptime",
+                        method: "GET",
+                        path: "/bots/{id}/uptime",
+                        path_params: &body(PATH_PARAMS, &models::FetchBotPath::default()),
+                        query_params: &body(QUERY_PARAMS, &models::UptimeQuery {
+                            range: Some(models::UptimeRange::Week),
+                        }),
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::BotUptime {
+                            range: models::UptimeRange::Week,
+                            checks: 2016,
+                            failed: 12,
+                            percentage: Some(99.4),
+                            incidents: vec![models::UptimeIncident::default()],
+                        }),
+                        description: r#"
+Returns the uptime of a bot over the last day, week or month 
+(``range`` is a [UptimeRange](https://lynx.fateslist.xyz/docs/endpoints/enums#uptimerange), 
+defaults to a day).
+
+Approved and certified bots are checked every 5 minutes. ``percentage`` is the percentage of 
+successful checks and is not set if the bot was not checked in this range. Checks during an 
+active maintenance [event](./bot-events#add-bot-event) are not counted.
+
+``incidents`` are outages (from the first failed check until the next successful one) that 
+overlap the range, newest first. Owners get a notification once an outage lasts an hour, 
+unless the bot is in maintenance.
+"#,
+                        auth_types: vec![]
+                    },
                     
                     models::Route {
                         title: "Add Bot",
@@ -767,7 +1100,10 @@ random value of the same type.
 With regards to ``extra_owners``, put all of them as a ``BotOwner`` object
 containing ``main`` set to ``false`` and ``user`` as a dummy ``user`` object 
 containing ``id`` filled in and the rest of a ``user``empty strings. Set ``bot``
-to false."#,
+to false.
+
+If the application info of the bot cannot be fetched right now, ``CheckBotError.AppInfoUnavailable`` 
+is returned and the bot should be added again later."#,
                         path_params: &body(PATH_PARAMS, &models::FetchBotPath { id: 0 }),
                         query_params: "",
                         request_body: &body(REQ_BODY, &models::Bot::default()),
@@ -931,17 +1267,62 @@ server privacy restrictions. **Note that when fetching invite links, requires lo
                         auth_types: vec![]
                     },
 
+                    models::Route {
+                        title: "Browse Servers",
+                        method: "GET",
+                        path: "/servers",
+                        path_params: "",
+                        query_params: &body(QUERY_PARAMS, &models::BrowseQuery {
+                            sort: Some(models::BrowseSort::Trending),
+                            state: Some(models::State::Certified),
+                            tags: Some("music|fun".to_string()),
+                            tag_mode: Some(models::TagMode::And),
+                            features: None,
+                            per_page: Some(25),
+                            cursor: None,
+                        }),
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::Browse {
+                            results: vec![models::IndexBot::default()],
+                            next_cursor: Some(browse_cursor.clone()),
+                        }),
+                        description: r#"
+Browses all servers on the list, page by page.
+
+``sort`` is a [BrowseSort](https://lynx.fateslist.xyz/docs/endpoints/enums#browsesort) and 
+defaults to ``Votes``. ``Trending`` uses votes over the last 7 days relative to all time votes.
+
+Servers do not track when they were last updated so ``RecentlyUpdated`` is the same as ``Newest``. 
+``features`` is ignored.
+
+``state`` defaults to approved and certified servers and can only be ``Approved`` or ``Certified``. ``tags`` is a ``|`` separated list of 
+tags and ``tag_mode`` (a [TagMode](https://lynx.fateslist.xyz/docs/endpoints/enums#tagmode)) 
+controls whether all or any of them must match.
+
+``per_page`` defaults to 25 and must be between 1 and 100.
+
+``next_cursor`` will be set if there may be more servers. Pass it back as ``cursor`` (with the 
+same ``sort``) to get the next page. Cursors are opaque and should not be parsed by clients."#,
+                        auth_types: vec![]
+                    },
+
                     models::Route {
                         title: "Random Server",
                         method: "GET",
                         path: "/random-server",
                         path_params: "",
-                        query_params: "",
+                        query_params: &body(QUERY_PARAMS, &models::RandomQuery {
+                            nsfw: Some(false),
+                        }),
                         request_body: "",
                         response_body: &body(RESP_BODY, &models::IndexBot::default()),
                         description: r#"
 Fetches a random server on the list
 
+NSFW servers are only returned if ``nsfw`` is set to ``true``
+
+Returns a 404 if no servers match
+
 Example:
 ```py
 import requests
@@ -1149,7 +1530,17 @@ Creates a bot pack.
 - Set ``id`` to empty string, 
 - Set ``created_at`` to any datetime
 - In user and bot, only ``id`` must be filled, all others can be left empty string
-but must exist in the object"#,
+but must exist in the object
+- A pack must have between 2 and 7 bots. Duplicate bots are removed
+- Bots are shown in the order given. Each bot may have a ``note`` of up to 200 characters
+- Banned, denied and NSFW bots cannot be added to packs. Bots that are deleted, banned or 
+denied later on are removed from packs automatically and the owner is notified. Packs left 
+with less than 2 bots are unlisted
+- ``banner`` must be a valid image url (``https`` only)
+- ``editors`` (at most 5) are other users who can edit the pack
+- ``visibility`` is a [PackVisibility](https://lynx.fateslist.xyz/docs/endpoints/enums#packvisibility). 
+Unlisted packs do not show up in search or on profiles
+- ``followers`` is ignored"#,
                         path_params: &body(PATH_PARAMS, &models::FetchBotPath { 
                             id: 0
                         }),
@@ -1173,7 +1564,10 @@ Edits a bot pack.
 - Set ``id`` to the pack id that is to be editted, 
 - Set ``created_at`` to any datetime
 - In user and bot, only ``id`` must be filled, all others can be left empty string
-but must exist in the object"#,
+but must exist in the object
+- Both the owner and editors can edit a pack but ``editors`` is only updated when 
+the owner edits the pack
+- ``owner`` and ``followers`` are ignored"#,
                         path_params: &body(PATH_PARAMS, &models::FetchBotPath { 
                             id: 0
                         }),
@@ -1211,6 +1605,409 @@ Deletes a bot pack.
                             context: None,
                         }),
                         auth_types: vec![models::RouteAuthType::User],
+                    },
+
+                    models::Route {
+                        title: "Get Pack",
+                        method: "GET",
+                        path: "/packs/{id}",
+                        description: r#"
+Gets a bot pack by its id. This also works for unlisted packs. 
+
+If not found, 404 will be returned."#,
+                        path_params: &body(PATH_PARAMS, &models::GetPackPath { 
+                            id: uuid::Uuid::new_v4().to_string()
+                        }),
+                        query_params: "",
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::BotPack {
+                            id: uuid::Uuid::new_v4().to_string(),
+                            editors: vec![models::User::default()],
+                            followers: 12,
+                            ..models::BotPack::default()
+                        }),
+                        auth_types: vec![],
+                    },
+
+                    models::Route {
+                        title: "Follow Pack",
+                        method: "PUT",
+                        path: "/users/{user_id}/packs/{pack_id}/follow",
+                        description: "Follows a bot pack. Following a pack twice does nothing",
+                        path_params: &body(PATH_PARAMS, &models::GetUserPackPath { 
+                            user_id: 0,
+                            pack_id: uuid::Uuid::new_v4().to_string()
+                        }),
+                        query_params: "",
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::APIResponse {
+                            done: true,
+                            reason: None,
+                            context: None,
+                        }),
+                        auth_types: vec![models::RouteAuthType::User],
+                    },
+
+                    models::Route {
+                        title: "Unfollow Pack",
+                        method: "DELETE",
+                        path: "/users/{user_id}/packs/{pack_id}/follow",
+                        description: "Unfollows a bot pack",
+                        path_params: &body(PATH_PARAMS, &models::GetUserPackPath { 
+                            user_id: 0,
+                            pack_id: uuid::Uuid::new_v4().to_string()
+                        }),
+                        query_params: "",
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::APIResponse {
+                            done: true,
+                            reason: None,
+                            context: None,
+                        }),
+                        auth_types: vec![models::RouteAuthType::User],
+                    }
+                ]
+            },
+
+            models::RouteList {
+                file_name: "commands.md",
+                routes: vec![
+                    models::Route {
+                        title: "Add Command",
+                        method: "POST",
+                        path: "/bots/{id}/commands",
+                        description: r#"
+Adds commands to a bot. Commands that already exist (same ``cmd_type`` and ``name``) are updated.
+
+``id`` in a command is ignored. Prefer [Sync Commands](#sync-commands) if you post all your 
+commands on startup.
+
+All commands posted to the commands endpoints are checked with the following rules, 
+each having its own error:
+
+- Slash command names may have up to 3 words (for subcommands) of 1 to 32 lowercase letters, 
+numbers, ``-`` and ``_``. Context menu command names are 1 to 32 characters and prefix command 
+names 1 to 64 characters. Neither may have control characters or leading/trailing spaces
+- Descriptions are 1 to 100 characters for slash and context menu commands and 1 to 500 
+characters for prefix commands
+- At most 25 ``args`` with unique names of 1 to 32 characters without spaces and at most 
+25 choices each. ``args`` may also be given as ``<required>`` or ``[optional]`` strings 
+which become string arguments
+- At most 10 ``examples`` and 10 ``notes`` of up to 200 characters each and 5 ``groups``
+- ``doc_link`` must be a ``https://`` link
+- A bot may have at most 250 commands"#,
+                        path_params: &body(PATH_PARAMS, &models::FetchBotPath { id: 0 }),
+                        query_params: "",
+                        request_body: &body(REQ_BODY, &models::BotCommandVec {
+                            commands: vec![models::BotCommand {
+                                cmd_type: models::CommandType::SlashCommandGlobal,
+                                groups: vec!["Default".to_string()],
+                                name: "ban".to_string(),
+                                description: "Bans a user".to_string(),
+                                args: vec![
+                                    models::CommandArg {
+                                        name: "user".to_string(),
+                                        arg_type: models::CommandArgType::User,
+                                        required: true,
+                                        choices: Vec::new(),
+                                    },
+                                    models::CommandArg {
+                                        name: "reason".to_string(),
+                                        arg_type: models::CommandArgType::String,
+                                        required: false,
+                                        choices: Vec::new(),
+                                    },
+                                ],
+                                doc_link: Some("https://docs.example.com/ban".to_string()),
+                                ..models::BotCommand::default()
+                            }],
+                        }),
+                        response_body: &body(RESP_BODY, &models::APIResponse {
+                            done: true,
+                            reason: None,
+                            context: None,
+                        }),
+                        auth_types: vec![models::RouteAuthType::Bot],
+                    },
+
+                    models::Route {
+                        title: "Delete Commands",
+                        method: "DELETE",
+                        path: "/bots/{id}/commands",
+                        description: r#"
+Deletes commands of a bot. 
+
+- If ``nuke`` is set, all commands are deleted
+- ``names`` and ``ids`` are ``|`` separated lists of command names and ids to delete"#,
+                        path_params: &body(PATH_PARAMS, &models::FetchBotPath { id: 0 }),
+                        query_params: &body(QUERY_PARAMS, &models::CommandDeleteQuery {
+                            nuke: Some(false),
+                            names: Some("help|ping".to_string()),
+                            ids: None,
+                        }),
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::APIResponse {
+                            done: true,
+                            reason: None,
+                            context: None,
+                        }),
+                        auth_types: vec![models::RouteAuthType::Bot],
+                    },
+
+                    models::Route {
+                        title: "Sync Commands",
+                        method: "PUT",
+                        path: "/bots/{id}/commands",
+                        description: r#"
+Replaces all commands of a bot with the ones given. 
+
+Commands are matched by ``cmd_type`` and ``name``. New commands are added, changed commands 
+are updated and commands not in the list are deleted. Everything is done at once so either all 
+changes are made or none are. A ``cmd_type`` and ``name`` pair may only be given once.
+
+Returns what was changed. ``CommandAdd`` events are sent for added and updated commands and 
+``CommandDelete`` events for deleted commands. Nothing is sent if nothing changed, so it is safe 
+to call this on every startup"#,
+                        path_params: &body(PATH_PARAMS, &models::FetchBotPath { id: 0 }),
+                        query_params: "",
+                        request_body: &body(REQ_BODY, &models::BotCommandVec {
+                            commands: vec![models::BotCommand::default()],
+                        }),
+                        response_body: &body(RESP_BODY, &models::CommandSyncSummary {
+                            added: vec![models::CommandSyncItem {
+                                cmd_type: models::CommandType::SlashCommandGlobal,
+                                name: "help".to_string(),
+                            }],
+                            updated: Vec::new(),
+                            deleted: vec![models::CommandSyncItem {
+                                cmd_type: models::CommandType::PrefixCommand,
+                                name: "oldhelp".to_string(),
+                            }],
+                            unchanged: 12,
+                        }),
+                        auth_types: vec![models::RouteAuthType::Bot],
+                    },
+
+                    models::Route {
+                        title: "Import Discord Commands",
+                        method: "POST",
+                        path: "/bots/{id}/commands/discord",
+                        description: r#"
+Imports commands from a list of Discord application commands. This is the same JSON you send 
+to Discord when registering your commands so you can post it here as is.
+
+- Every subcommand becomes its own command named after its full path (such as ``config set``) 
+and is grouped under its parent command (or ``command group`` for subcommand groups, just 
+``group`` if that is longer than 32 characters)
+- Commands without subcommands are put in the ``Default`` group
+- ``args`` are made from the options of a command with their type, whether they are required 
+and the names of their choices. Option descriptions are combined into ``notes``, descriptions 
+that do not fit in 10 notes are left out
+- Commands with a ``guild_id`` are guild commands, all others are global commands
+- Context menu commands are ``ContextMenuCommand`` commands in the ``Context Menu`` group
+- Localizations are accepted but not stored
+
+By default the imported commands are added or updated and all other commands are kept. 
+If ``replace`` is set, slash commands not in the import are deleted. Prefix commands are 
+never touched by an import. Returns what was changed, just like 
+[Sync Commands](#sync-commands)"#,
+                        path_params: &body(PATH_PARAMS, &models::FetchBotPath { id: 0 }),
+                        query_params: &body(QUERY_PARAMS, &models::CommandImportQuery {
+                            replace: Some(false),
+                        }),
+                        request_body: &body(REQ_BODY, &vec![models::DiscordAppCommand {
+                            command_type: Some(1),
+                            name: "config".to_string(),
+                            description: "Configure the bot".to_string(),
+                            options: Some(vec![models::DiscordAppCommandOption {
+                                option_type: 1,
+                                name: "prefix".to_string(),
+                                description: "Set the prefix".to_string(),
+                                options: Some(vec![models::DiscordAppCommandOption {
+                                    option_type: 3,
+                                    name: "prefix".to_string(),
+                                    description: "The new prefix".to_string(),
+                                    required: Some(true),
+                                    ..models::DiscordAppCommandOption::default()
+                                }]),
+                                ..models::DiscordAppCommandOption::default()
+                            }]),
+                            ..models::DiscordAppCommand::default()
+                        }]),
+                        response_body: &body(RESP_BODY, &models::CommandSyncSummary {
+                            added: vec![models::CommandSyncItem {
+                                cmd_type: models::CommandType::SlashCommandGlobal,
+                                name: "config prefix".to_string(),
+                            }],
+                            updated: Vec::new(),
+                            deleted: Vec::new(),
+                            unchanged: 4,
+                        }),
+                        auth_types: vec![models::RouteAuthType::Bot],
+                    },
+
+                    models::Route {
+                        title: "Search Commands",
+                        method: "GET",
+                        path: "/commands/search",
+                        description: r#"
+Searches the commands of all approved and certified bots. A leading ``/`` in ``q`` is ignored 
+so searching for ``/poll`` works.
+
+Commands match if ``q`` is part of their name or matches their description. Commands named 
+exactly ``q`` come first, all others are ranked by the votes of their bot.
+
+- ``cmd_type`` only returns commands of that [CommandType](https://lynx.fateslist.xyz/docs/endpoints/enums#commandtype)
+- ``nsfw`` only returns commands that are (or are not) NSFW and defaults to ``false``. A command 
+is NSFW if it is marked NSFW or its bot is NSFW
+- ``premium_only`` and ``vote_locked`` filter on the flags of the command
+- ``per_page`` must be between 1 and 50 and defaults to 12"#,
+                        path_params: "",
+                        query_params: &body(QUERY_PARAMS, &models::CommandSearchQuery {
+                            q: "poll".to_string(),
+                            cmd_type: Some(models::CommandType::SlashCommandGlobal),
+                            nsfw: Some(false),
+                            premium_only: None,
+                            vote_locked: None,
+                            page: Some(1),
+                            per_page: Some(12),
+                        }),
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::CommandSearch {
+                            results: vec![models::CommandSearchResult {
+                                command: models::BotCommand {
+                                    cmd_type: models::CommandType::SlashCommandGlobal,
+                                    groups: vec!["Default".to_string()],
+                                    name: "poll".to_string(),
+                                    description: "Creates a poll".to_string(),
+                                    id: Some(uuid::Uuid::new_v4().to_string()),
+                                    ..models::BotCommand::default()
+                                },
+                                bot: models::IndexBot::default(),
+                            }],
+                            total: 1,
+                            per_page: 12,
+                            from: 0,
+                        }),
+                        auth_types: vec![],
+                    }
+                ]
+            },
+
+            models::RouteList {
+                file_name: "bot-events.md",
+                routes: vec![
+                    models::Route {
+                        title: "Get Bot Events",
+                        method: "GET",
+                        path: "/users/{user_id}/bots/{bot_id}/events",
+                        description: r#"
+Gets all events of a bot including scheduled and ended ones. You must be an owner of the bot.
+
+``events`` in [Get Bot](./bot-actions#get-bot) only has active events"#,
+                        path_params: &body(PATH_PARAMS, &models::GetUserBotPath {
+                            user_id: 0,
+                            bot_id: 0,
+                        }),
+                        query_params: "",
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &vec![models::BotEvent::default()]),
+                        auth_types: vec![models::RouteAuthType::User],
+                    },
+
+                    models::Route {
+                        title: "Add Bot Event",
+                        method: "POST",
+                        path: "/users/{user_id}/bots/{bot_id}/events",
+                        description: r#"
+Adds an event to a bot. You must be an owner of the bot. ``context`` of the response is the id 
+of the new event.
+
+- ``reason`` must be between 1 and 1000 characters
+- ``css`` may be at most 10000 characters and is sanitized with ammonia the same way as the 
+``css`` of a bot
+- ``starts_at`` defaults to now. Events starting later are scheduled
+- ``ends_at`` is optional, events without it stay active until they are deleted. It must be 
+in the future and after ``starts_at``
+- A bot may have at most 10 active or scheduled events
+- ``pause_invites`` pauses invites while the event is active. It is only used for ``Maintenance`` 
+events. Failed uptime checks during an active maintenance event are not counted against the bot
+
+A ``BotEventStart`` websocket event (with the event as its props) is sent once the event 
+starts and a ``BotEventEnd`` event (with the ``id`` and ``event_type`` of the event) once it 
+ends, which is also when maintenance ends. Both may be up to a minute late"#,
+                        path_params: &body(PATH_PARAMS, &models::GetUserBotPath {
+                            user_id: 0,
+                            bot_id: 0,
+                        }),
+                        query_params: "",
+                        request_body: &body(REQ_BODY, &models::BotEventData {
+                            event_type: models::BotEventType::Maintenance,
+                            reason: "Upgrading our servers".to_string(),
+                            css: String::new(),
+                            starts_at: Some(chrono::Utc::now()),
+                            ends_at: Some(chrono::Utc::now() + chrono::Duration::hours(2)),
+                            pause_invites: true,
+                        }),
+                        response_body: &body(RESP_BODY, &models::APIResponse {
+                            done: true,
+                            reason: None,
+                            context: Some(uuid::Uuid::new_v4().to_string()),
+                        }),
+                        auth_types: vec![models::RouteAuthType::User],
+                    },
+
+                    models::Route {
+                        title: "Edit Bot Event",
+                        method: "PATCH",
+                        path: "/users/{user_id}/bots/{bot_id}/events/{event_id}",
+                        description: r#"
+Edits an event of a bot. The same rules as [Add Bot Event](#add-bot-event) apply and 
+``starts_at`` is kept if not given, ``ends_at`` must then still be after the kept start. 
+
+If the event was active, ``BotEventEnd`` is sent and ``BotEventStart`` is sent again with the 
+new event once it is active"#,
+                        path_params: &body(PATH_PARAMS, &models::GetUserBotEventPath {
+                            user_id: 0,
+                            bot_id: 0,
+                            event_id: uuid::Uuid::new_v4().to_string(),
+                        }),
+                        query_params: "",
+                        request_body: &body(REQ_BODY, &models::BotEventData {
+                            event_type: models::BotEventType::Announcement,
+                            reason: "We now support slash commands!".to_string(),
+                            css: String::new(),
+                            starts_at: None,
+                            ends_at: None,
+                            pause_invites: false,
+                        }),
+                        response_body: &body(RESP_BODY, &models::APIResponse {
+                            done: true,
+                            reason: None,
+                            context: None,
+                        }),
+                        auth_types: vec![models::RouteAuthType::User],
+                    },
+
+                    models::Route {
+                        title: "Delete Bot Event",
+                        method: "DELETE",
+                        path: "/users/{user_id}/bots/{bot_id}/events/{event_id}",
+                        description: r#"
+Deletes an event of a bot. ``BotEventEnd`` is sent if the event was active"#,
+                        path_params: &body(PATH_PARAMS, &models::GetUserBotEventPath {
+                            user_id: 0,
+                            bot_id: 0,
+                            event_id: uuid::Uuid::new_v4().to_string(),
+                        }),
+                        query_params: "",
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::APIResponse {
+                            done: true,
+                            reason: None,
+                            context: None,
+                        }),
+                        auth_types: vec![models::RouteAuthType::User],
                     }
                 ]
             },
@@ -1254,7 +2051,11 @@ Internally is used by client for extra owner rendering etc."#,
                         title: "Get Profile",
                         method: "GET",
                         path: "/profiles/{id}",
-                        description: "Gets a user profile.",
+                        description: r#"
+Gets a user profile.
+
+Unlisted packs are only included in ``packs`` if the users token is given in ``Authorization``.
+"#,
                         path_params: &body(PATH_PARAMS, &models::FetchBotPath { id: 0 }),
                         query_params: "",
                         request_body: "",
@@ -1264,7 +2065,7 @@ Internally is used by client for extra owner rendering etc."#,
                             connections: vec![models::FrostpawUserConnection::default()],
                             ..models::Profile::default()
                         }),
-                        auth_types: vec![],
+                        auth_types: vec![models::RouteAuthType::User],
                     },
 
                     models::Route {
@@ -1329,8 +2130,26 @@ path.
 ``user_id`` is optional for this endpoint but specifying it will provide ``user_reviews`` if
 the user has made a review. This will tell you the users review for the entity.
 
-``per_page`` (amount of root/non-reply reviews per page) is currently set to 9. 
-This may change in the future and is given by ``per_page`` key.
+``per_page`` (amount of root/non-reply reviews per page) defaults to 9 and must be 
+between 1 and 50. The value used is given by the ``per_page`` key.
+
+``sort`` is a [ReviewSort](https://lynx.fateslist.xyz/docs/endpoints/enums#reviewsort) and 
+defaults to ``Newest``.
+
+``stars_from`` and ``stars_to`` can be used to only get reviews within a star rating range
+(inclusive). ``stars_from`` must not be greater than ``stars_to``.
+
+``next_cursor`` will be set if there may be more reviews. Pass it back as ``cursor`` (with the 
+same ``sort``) to get the next page. When ``cursor`` is set, ``page`` is ignored. Cursors are 
+opaque and should not be parsed by clients.
+
+``stats`` contains the review stats of the entity. ``histogram`` has 11 buckets where 
+bucket ``n`` is the amount of reviews with a star rating from ``n`` to below ``n + 1``. 
+``average_stars_30d`` and ``average_stars_90d`` are the average star ratings of reviews made 
+in the last 30 and 90 days. ``owner_responses`` is the amount of reviews that an owner of 
+the entity has replied to. Stats may be cached for up to 10 minutes.
+
+Replies are only loaded up to 5 levels deep and at most 25 replies are loaded per review.
 
 ``from`` contains the index/count of the first review of the page."#,
                         path_params: &body(PATH_PARAMS, &models::FetchBotPath { id: 0 }),
@@ -1338,6 +2157,11 @@ This may change in the future and is given by ``per_page`` key.
                             page: Some(1),
                             user_id: Some(0),
                             target_type: models::TargetType::Bot,
+                            sort: Some(models::ReviewSort::MostHelpful),
+                            stars_from: Some(5),
+                            stars_to: Some(10),
+                            per_page: Some(9),
+                            cursor: None,
                         }),
                         request_body: "",
                         response_body: &body(RESP_BODY, &models::ParsedReview {
@@ -1348,7 +2172,12 @@ This may change in the future and is given by ``per_page`` key.
                             stats: models::ReviewStats {
                                 total: 78,
                                 average_stars: bigdecimal::BigDecimal::from_f32(8.8).unwrap(),
+                                histogram: vec![0, 1, 0, 2, 1, 3, 4, 10, 17, 25, 15],
+                                average_stars_30d: bigdecimal::BigDecimal::from_f32(9.1).unwrap(),
+                                average_stars_90d: bigdecimal::BigDecimal::from_f32(8.6).unwrap(),
+                                owner_responses: 12,
                             },
+                            next_cursor: Some("MnwzfDAwMDAwMDAwLTAwMDAtMDAwMC0wMDAwLTAwMDAwMDAwMDAwMA".to_string()),
                         }),
                         auth_types: vec![],
                     },
@@ -1373,12 +2202,19 @@ The ``parent_id`` is optional and is used to create a reply to a review.
 ``review`` is a [Review](https://lynx.fateslist.xyz/docs/endpoints/enums#review)
 
 ``user_id`` is *required* for this endpoint and must be the user making the review. It must
-also match the user token sent in the ``Authorization`` header"#,
+also match the user token sent in the ``Authorization`` header
+
+Reviews go through content checks before being saved. Reviews that are duplicates of your 
+other reviews, contain too many links, too many repeated characters or a banned phrase, or are 
+made from a very new account are rejected with a ``ReviewAddError``. Borderline reviews 
+(all caps, a few links etc.) are saved but automatically flagged. The same checks are run
+when editing a review."#,
                         path_params: &body(PATH_PARAMS, &models::FetchBotPath { id: 0 }),
                         query_params: &body(QUERY_PARAMS, &models::ReviewQuery {
                             page: None,
                             user_id: Some(0),
                             target_type: models::TargetType::Bot,
+                            ..models::ReviewQuery::default()
                         }),
                         request_body: &body(REQ_BODY, &models::Review {
                             parent_id: Some(uuid::Uuid::new_v4()),
@@ -1421,6 +2257,7 @@ also match the user token sent in the ``Authorization`` header"#,
                             page: None,
                             user_id: Some(0),
                             target_type: models::TargetType::Bot,
+                            ..models::ReviewQuery::default()
                         }),
                         request_body: &body(REQ_BODY, &models::Review {
                             id: Some(uuid::Uuid::new_v4()),
@@ -1460,6 +2297,7 @@ set this anyways so you might as well set it correctly."#,
                             page: None,
                             user_id: Some(0),
                             target_type: models::TargetType::Bot,
+                            ..models::ReviewQuery::default()
                         }),
                         request_body: "",
                         response_body: &body(RESP_BODY, &models::APIResponse {
@@ -1505,6 +2343,38 @@ in the future.
                             context: None,
                         }),
                         auth_types: vec![models::RouteAuthType::User],
+                    },
+
+                    models::Route {
+                        title: "Get Review History",
+                        method: "GET",
+                        path: "/reviews/{rid}/history",
+                        description: r#"
+Gets the edit history of a review. ``review`` is the current version of the review and
+``revisions`` contains all prior revisions of the review (newest first).
+
+A new revision is saved every time the review is edited.
+
+``rid`` must be a valid uuid.
+
+``user_id`` is *required* for this endpoint and must be either the user who made the review,
+an owner of the reviewed entity or staff. It must also match the user token sent in the 
+``Authorization`` header.
+
+``target_type`` is not currently checked but must still be set."#,
+                        path_params: &body(PATH_PARAMS, &models::ReviewDeletePath {
+                            rid: uuid::Uuid::new_v4().to_hyphenated().to_string(),
+                        }),
+                        query_params: &body(QUERY_PARAMS, &models::ReviewQuery {
+                            user_id: Some(0),
+                            ..models::ReviewQuery::default()
+                        }),
+                        request_body: "",
+                        response_body: &body(RESP_BODY, &models::ReviewHistory {
+                            review: models::Review::default(),
+                            revisions: vec![models::ReviewRevision::default()],
+                        }),
+                        auth_types: vec![models::RouteAuthType::User],
                     }
                 ]
             }
@@ -1672,6 +2542,62 @@ It is semi-automatically generated
         },
     });
 
+    // StatsRange
+    docs += &new_enum(models::EnumDesc {
+        name: "StatsRange",
+        alt_names: vec!["range (stats history)"],
+        description: "The time range to get the stats history of a bot for",
+        gen: || {
+            let mut types = String::new();
+            for typ in models::StatsRange::iter() {
+                types += &enum_doc(typ);
+            }
+            types
+        },
+    });
+
+    // UptimeRange
+    docs += &new_enum(models::EnumDesc {
+        name: "UptimeRange",
+        alt_names: vec!["range"],
+        description: "The time range to get the uptime of a bot for",
+        gen: || {
+            let mut types = String::new();
+            for typ in models::UptimeRange::iter() {
+                types += &enum_doc(typ);
+            }
+            types
+        },
+    });
+
+    // BotEventType
+    docs += &new_enum(models::EnumDesc {
+        name: "BotEventType",
+        alt_names: vec!["event_type"],
+        description: "The type of a bot event",
+        gen: || {
+            let mut types = String::new();
+            for typ in models::BotEventType::iter() {
+                types += &enum_doc(typ);
+            }
+            types
+        },
+    });
+
+    // CommandArgType
+    docs += &new_enum(models::EnumDesc {
+        name: "CommandArgType",
+        alt_names: vec!["arg_type"],
+        description: "The type of value a command argument takes",
+        gen: || {
+            let mut types = String::new();
+            for typ in models::CommandArgType::iter() {
+                types += &enum_doc(typ);
+            }
+            types
+        },
+    });
+
     // ImportSource
     docs += &new_enum(models::EnumDesc {
         name: "ImportSource",
@@ -1756,6 +2682,34 @@ It is semi-automatically generated
         },
     });
 
+    // SuggestionType
+    docs += &new_enum(models::EnumDesc {
+        name: "SuggestionType",
+        alt_names: vec!["suggestion_type"],
+        description: "The type of a search suggestion",
+        gen: || {
+            let mut types = String::new();
+            for typ in models::SuggestionType::iter() {
+                types += &enum_doc(typ);
+            }
+            types
+        },
+    });
+
+    // TagMode
+    docs += &new_enum(models::EnumDesc {
+        name: "TagMode",
+        alt_names: vec!["tag_mode"],
+        description: "Whether a search must match all or any of the given tags",
+        gen: || {
+            let mut types = String::new();
+            for typ in models::TagMode::iter() {
+                types += &enum_doc(typ);
+            }
+            types
+        },
+    });
+
     // TargetType
     docs += &new_enum(models::EnumDesc {
         name: "TargetType",
@@ -1770,6 +2724,48 @@ It is semi-automatically generated
         },
     });
 
+    // ReviewSort
+    docs += &new_enum(models::EnumDesc {
+        name: "ReviewSort",
+        alt_names: vec!["sort"],
+        description: "How reviews should be sorted",
+        gen: || {
+            let mut types = String::new();
+            for typ in models::ReviewSort::iter() {
+                types += &enum_doc(typ);
+            }
+            types
+        },
+    });
+
+    // BrowseSort
+    docs += &new_enum(models::EnumDesc {
+        name: "BrowseSort",
+        alt_names: vec!["sort"],
+        description: "How bots and servers should be sorted when browsing",
+        gen: || {
+            let mut types = String::new();
+            for typ in models::BrowseSort::iter() {
+                types += &enum_doc(typ);
+            }
+            types
+        },
+    });
+
+    // PackVisibility
+    docs += &new_enum(models::EnumDesc {
+        name: "PackVisibility",
+        alt_names: vec!["visibility"],
+        description: "Who can find a bot pack",
+        gen: || {
+            let mut types = String::new();
+            for typ in models::PackVisibility::iter() {
+                types += &enum_doc(typ);
+            }
+            types
+        },
+    });
+
     docs += "To see errors, please see https://github.com/Fates-List/api-v3/blob/main/src/models.rs and search for all ``APIError`` trait implementations";
 
     let path = match std::env::var_os("HOME") {
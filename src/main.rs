@@ -13,7 +13,9 @@ use log::{debug, error, info};
 use std::sync::Arc;
 
 mod appeal;
+mod appinfo;
 mod botactions;
+mod botevents;
 mod serveractions;
 mod commands;
 mod converters;
@@ -23,9 +25,13 @@ mod docs;
 mod login;
 mod models;
 mod packs;
+mod ratelimit;
 mod reviews;
+mod reviewfilter;
 mod security;
 mod stats;
+mod tasks;
+mod uptime;
 mod user;
 mod ws;
 mod votes;
@@ -54,17 +60,17 @@ async fn main() -> std::io::Result<()> {
 
     let app_config = models::AppConfig::default();
 
-    let discord_main = app_config.discord_http;
+    /* Arc is used here for discord to provide shared ownership which is needed by discord integration support
+    Cost for Arc is negligible here
+    */
+    let discord_main = Arc::new(app_config.discord_http);
     let discord_server = app_config.discord_http_server;
 
     let pool = database::Database::new(
         7,
         "postgres://localhost/fateslist",
         "redis://127.0.0.1:1001/1",
-        /* Arc is used here for discord to provide shared ownership which is needed by discord integration support
-        Cost for Arc is negligible here
-        */
-        Arc::new(discord_main),
+        discord_main.clone(),
         Arc::new(discord_server)
     )
     .await;
@@ -76,12 +82,19 @@ async fn main() -> std::io::Result<()> {
         .build()
         .unwrap();
 
+    let config = models::AppConfig::default();
+
+    let app_info = appinfo::AppInfo::new(&config, appinfo::provider(&config, discord_main, client.clone()));
+
     let app_state = web::Data::new(models::AppState {
         database: pool,
-        config: models::AppConfig::default(),
+        config,
         requests: client,
+        app_info,
     });
 
+    tasks::start(&app_state);
+
     docs::document_routes();
     docs::document_enums();
 
@@ -109,6 +122,12 @@ async fn main() -> std::io::Result<()> {
                 http::header::HeaderName::from_bytes(b"Frostpaw-Invite").unwrap(),
                 http::header::HeaderName::from_bytes(b"Method").unwrap(),
             ])
+            .expose_headers(vec![
+                http::header::RETRY_AFTER,
+                http::header::HeaderName::from_bytes(b"X-RateLimit-Limit").unwrap(),
+                http::header::HeaderName::from_bytes(b"X-RateLimit-Remaining").unwrap(),
+                http::header::HeaderName::from_bytes(b"X-RateLimit-Reset").unwrap(),
+            ])
             .supports_credentials()
             .max_age(3600);
         App::new()
@@ -120,6 +139,8 @@ async fn main() -> std::io::Result<()> {
             )
             .app_data(web::QueryConfig::default().error_handler(|err, _req| actix_handle_err(err)))
             .app_data(web::PathConfig::default().error_handler(|err, _req| actix_handle_err(err)))
+            // Inside cors so rate limited responses still have cors headers
+            .wrap(ratelimit::Ratelimiter)
             .wrap(cors)
             .wrap(middleware::Compress::default())
             .wrap(Logger::default())
@@ -154,6 +175,7 @@ async fn main() -> std::io::Result<()> {
             .default_service(web::route().to(not_found))
             // Core
             .service(core::index)
+            .service(core::trending)
             .service(core::ping)
             .service(core::mini_index) // Used Add Bot
             .service(core::resolve_vanity)
@@ -161,6 +183,9 @@ async fn main() -> std::io::Result<()> {
             .service(core::get_partners)
             .service(core::search_list)
             .service(core::search_tags)
+            .service(core::search_suggest)
+            .service(core::search_profiles)
+            .service(core::search_packs)
 
             // Votes
             .service(votes::create_bot_vote)
@@ -189,13 +214,18 @@ async fn main() -> std::io::Result<()> {
             .service(botactions::get_import_sources)
             .service(botactions::get_bot)
             .service(botactions::random_bot)
+            .service(botactions::browse_bots)
             .service(botactions::post_stats)            
             .service(botactions::get_bot_settings)
+            .service(botactions::get_similar_bots)
+            .service(botactions::get_bot_uptime)
+            .service(botactions::get_stats_history)
 
 
             // Server Actions
             .service(serveractions::get_server)
             .service(serveractions::random_server)
+            .service(serveractions::browse_servers)
 
             // Appeal
             .service(appeal::appeal_bot)
@@ -205,6 +235,9 @@ async fn main() -> std::io::Result<()> {
             .service(packs::add_pack)
             .service(packs::edit_pack)
             .service(packs::delete_pack)
+            .service(packs::get_pack)
+            .service(packs::follow_pack)
+            .service(packs::unfollow_pack)
 
             // User
             .service(user::get_user_from_id)
@@ -219,6 +252,7 @@ async fn main() -> std::io::Result<()> {
             .service(reviews::edit_review)
             .service(reviews::delete_review)
             .service(reviews::vote_review)
+            .service(reviews::get_review_history)
 
             // Stats
             .service(stats::get_botlist_stats)
@@ -226,6 +260,13 @@ async fn main() -> std::io::Result<()> {
             // Commands
             .service(commands::add_command)
             .service(commands::delete_commands)
+            .service(commands::sync_commands)
+            .service(commands::import_discord_commands)
+            .service(commands::search_commands)
+            .service(botevents::get_bot_events)
+            .service(botevents::add_bot_event)
+            .service(botevents::edit_bot_event)
+            .service(botevents::delete_bot_event)
 
             // WS
             .service(ws::preview_description)
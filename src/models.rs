@@ -1,3 +1,4 @@
+use crate::appinfo;
 use crate::database;
 use actix_web::HttpResponse;
 use log::{debug, error};
@@ -96,6 +97,24 @@ pub enum CommandType {
     PrefixCommand = 0,
     SlashCommandGlobal = 1,
     SlashCommandGuild = 2,
+    ContextMenuCommand = 3,
+}
+
+#[derive(
+    Eq, TryFromPrimitive, Serialize_repr, Deserialize_repr, PartialEq, Clone, Copy, Default, Debug, EnumIter
+)]
+#[repr(i32)]
+pub enum CommandArgType {
+    #[default]
+    String = 0,
+    Integer = 1,
+    Boolean = 2,
+    User = 3,
+    Channel = 4,
+    Role = 5,
+    Mentionable = 6,
+    Number = 7,
+    Attachment = 8,
 }
 
 #[derive(
@@ -164,6 +183,9 @@ pub struct IndexBot {
     pub user: User,
     pub flags: Vec<i32>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    pub review_stats: Option<ReviewStats>,
+    /// Only set for search results
+    pub search_meta: Option<SearchMeta>,
 }
 
 impl Default for IndexBot {
@@ -177,6 +199,8 @@ impl Default for IndexBot {
             user: User::default(),
             flags: vec![],
             created_at: chrono::Utc::now(),
+            review_stats: Some(ReviewStats::default()),
+            search_meta: None,
         }
     }
 }
@@ -216,6 +240,9 @@ pub struct Index {
     pub certified: Vec<IndexBot>,
     pub tags: Vec<Tag>,
     pub features: Vec<Feature>,
+    /// Only set for logged in users on the bot index
+    pub recommended: Vec<IndexBot>,
+    pub trending: Trending,
 }
 
 impl Index {
@@ -226,10 +253,32 @@ impl Index {
             new: Vec::new(),
             tags: Vec::new(),
             features: Vec::new(),
+            recommended: Vec::new(),
+            trending: Trending::default(),
         }
     }
 }
 
+/// Bots/servers ranked by how fast they are getting votes and views, relative to their size
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct Trending {
+    /// Last 24 hours
+    pub day: Vec<IndexBot>,
+    /// Last 7 days
+    pub week: Vec<IndexBot>,
+}
+
+#[derive(
+    Eq, TryFromPrimitive, Serialize_repr, Deserialize_repr, PartialEq, Clone, Copy, Default, Debug, EnumIter
+)]
+#[repr(i32)]
+pub enum PackVisibility {
+    #[default]
+    Public = 0,
+    /// Only reachable through ``GET /packs/{id}``
+    Unlisted = 1,
+}
+
 #[derive(Deserialize, Serialize, Clone)]
 pub struct BotPack {
     pub id: String,
@@ -237,8 +286,17 @@ pub struct BotPack {
     pub description: String,
     pub icon: String,
     pub banner: String,
+    /// In display order
     pub resolved_bots: Vec<ResolvedPackBot>,
     pub owner: User,
+    /// Users other than the owner who may edit the pack. Only the owner can change this
+    #[serde(default)]
+    pub editors: Vec<User>,
+    #[serde(default)]
+    pub visibility: PackVisibility,
+    /// Ignored when adding or editing a pack
+    #[serde(default)]
+    pub followers: i64,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
@@ -252,6 +310,9 @@ impl Default for BotPack {
             banner: "".to_string(),
             resolved_bots: vec![ResolvedPackBot::default()],
             owner: User::default(),
+            editors: Vec::new(),
+            visibility: PackVisibility::Public,
+            followers: 0,
             created_at: chrono::DateTime::<chrono::Utc>::from_utc(
                 chrono::NaiveDateTime::from_timestamp(0, 0),
                 chrono::Utc,
@@ -263,6 +324,17 @@ impl Default for BotPack {
 pub struct ResolvedPackBot {
     pub user: User,
     pub description: String,
+    /// Why this bot is in the pack, for example "use this for moderation"
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct SearchMeta {
+    pub rank: f32,
+    /// Part of the description matching the query. Matches are wrapped in ``<mark>`` tags
+    /// and the rest of the snippet is HTML escaped
+    pub snippet: String,
 }
 
 #[derive(Deserialize, Serialize, Clone, Default)]
@@ -278,6 +350,18 @@ pub struct SearchTags {
     pub servers: Vec<Tag>,
 }
 
+/// Facet counts of the bots or servers matching a search
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct SearchFacets {
+    pub total: i64,
+    pub tags: IndexMap<String, i64>,
+    pub features: IndexMap<String, i64>,
+    pub libraries: IndexMap<String, i64>,
+    pub page_styles: IndexMap<String, i64>,
+    pub certified: i64,
+    pub nsfw: i64,
+}
+
 #[derive(Deserialize, Serialize, Clone, Default)]
 pub struct Search {
     pub bots: Vec<IndexBot>,
@@ -285,11 +369,44 @@ pub struct Search {
     pub profiles: Vec<SearchProfile>,
     pub packs: Vec<BotPack>,
     pub tags: SearchTags,
+    pub facets: SearchFacets,
+    pub server_facets: SearchFacets,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct TrendingQuery {
+    pub target_type: TargetType,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
 pub struct IndexQuery {
     pub target_type: TargetType,
+    /// Set this (along with the users token in ``Authorization``) to get recommendations
+    pub user_id: Option<i64>,
+    /// Size of each section, defaults to 12
+    pub limit: Option<i64>,
+    /// Defaults to the users NSFW preference if logged in, otherwise NSFW entities are included
+    pub nsfw: Option<bool>,
+    /// Requires ``user_id``
+    pub exclude_voted: Option<bool>,
+}
+
+/// Resolved index options. Also used (with the target type) as the index cache key
+#[derive(Hash, Eq, PartialEq, Clone, Copy)]
+pub struct IndexOptions {
+    pub limit: i64,
+    pub nsfw: bool,
+    pub exclude_voted_by: Option<i64>,
+}
+
+impl Default for IndexOptions {
+    fn default() -> Self {
+        IndexOptions {
+            limit: 12,
+            nsfw: true,
+            exclude_voted_by: None,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -331,6 +448,11 @@ pub struct GetUserPackPath {
     pub pack_id: String,
 }
 
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct GetPackPath {
+    pub id: String,
+}
+
 #[derive(Deserialize, Serialize, Clone, Default)]
 pub struct PreviewRequest {
     pub text: String,
@@ -446,6 +568,79 @@ pub struct BotStats {
     pub shard_count: Option<i64>,
     pub shards: Option<Vec<i32>>,
     pub user_count: Option<i64>,
+    /// Guild count of each shard, the index is the shard id
+    #[serde(default)]
+    pub shard_guilds: Option<Vec<i64>>,
+}
+
+#[derive(
+    Eq, TryFromPrimitive, Serialize_repr, Deserialize_repr, PartialEq, Clone, Copy, Default, Debug, EnumIter
+)]
+#[repr(i32)]
+pub enum StatsRange {
+    Day = 0,
+    #[default]
+    Week = 1,
+    Month = 2,
+    Year = 3,
+}
+
+impl StatsRange {
+    pub fn days(self) -> i32 {
+        match self {
+            Self::Day => 1,
+            Self::Week => 7,
+            Self::Month => 30,
+            Self::Year => 365,
+        }
+    }
+
+    /// The size of a point in the history, passed to ``date_trunc``
+    pub fn bucket(self) -> &'static str {
+        match self {
+            Self::Day | Self::Week => "hour",
+            Self::Month | Self::Year => "day",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct StatsHistoryQuery {
+    pub range: Option<StatsRange>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct StatsPoint {
+    /// Start of the hour or day of this point, the last post in it is used
+    pub ts: chrono::DateTime<chrono::Utc>,
+    pub guild_count: i64,
+    pub shard_count: Option<i64>,
+    pub user_count: Option<i64>,
+    pub shard_guilds: Option<Vec<i64>>,
+    /// Why this post looked suspicious, if it did
+    pub anomaly: Option<String>,
+}
+
+impl Default for StatsPoint {
+    fn default() -> Self {
+        StatsPoint {
+            ts: chrono::Utc::now(),
+            guild_count: 3939,
+            shard_count: Some(3),
+            user_count: Some(39393),
+            shard_guilds: Some(vec![1300, 1320, 1319]),
+            anomaly: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct StatsHistory {
+    pub range: StatsRange,
+    /// Oldest first
+    pub points: Vec<StatsPoint>,
+    /// Guild count change over the range
+    pub growth: i64,
 }
 
 /// The response from the oauth2 endpoint. We do not care about anything but access token
@@ -464,6 +659,114 @@ pub struct SearchQuery {
     pub q: String,
     pub gc_from: i64,
     pub gc_to: i64,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+    /// Tags seperated by ``|``
+    pub tags: Option<String>,
+    pub tag_mode: Option<TagMode>,
+    /// Feature ids seperated by ``|``. Only applies to bots
+    pub features: Option<String>,
+    /// Only applies to bots
+    pub library: Option<String>,
+    /// Only applies to bots
+    pub page_style: Option<PageStyle>,
+    pub certified_only: Option<bool>,
+    /// ``true`` for only NSFW, ``false`` to exclude NSFW and omitted for both
+    pub nsfw: Option<bool>,
+    pub created_after: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(
+    Eq, TryFromPrimitive, Serialize_repr, Deserialize_repr, PartialEq, Clone, Copy, Default, Debug, EnumIter
+)]
+#[repr(i32)]
+pub enum TagMode {
+    /// Must have all of the tags
+    #[default]
+    And = 0,
+    /// Must have at least one of the tags
+    Or = 1,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct RandomQuery {
+    pub nsfw: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct PagedSearchQuery {
+    pub q: String,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct ProfileSearch {
+    pub profiles: Vec<SearchProfile>,
+    pub total: i64,
+    pub per_page: i64,
+    pub from: i64,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct PackSearch {
+    pub packs: Vec<BotPack>,
+    pub total: i64,
+    pub per_page: i64,
+    pub from: i64,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct CommandSearchQuery {
+    pub q: String,
+    pub cmd_type: Option<CommandType>,
+    /// Matches both NSFW commands and commands of NSFW bots. Defaults to false
+    pub nsfw: Option<bool>,
+    pub premium_only: Option<bool>,
+    pub vote_locked: Option<bool>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct CommandSearchResult {
+    pub command: BotCommand,
+    pub bot: IndexBot,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct CommandSearch {
+    pub results: Vec<CommandSearchResult>,
+    pub total: i64,
+    pub per_page: i64,
+    pub from: i64,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct SuggestQuery {
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(
+    Eq, TryFromPrimitive, Serialize_repr, Deserialize_repr, PartialEq, Clone, Copy, Default, Debug, EnumIter
+)]
+#[repr(i32)]
+pub enum SuggestionType {
+    #[default]
+    Bot = 0,
+    Server = 1,
+    Pack = 2,
+    BotTag = 3,
+    ServerTag = 4,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct Suggestion {
+    pub id: String,
+    pub name: String,
+    pub vanity: Option<String>,
+    pub suggestion_type: SuggestionType,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -500,11 +803,109 @@ pub struct ListStats {
     pub total_bots: i64,
     pub total_servers: i64,
     pub total_users: i64,
-    pub bots: Vec<IndexBot>,
-    pub servers: Vec<IndexBot>,
     pub uptime: f64,
 }
 
+#[derive(
+    Eq, TryFromPrimitive, Serialize_repr, Deserialize_repr, PartialEq, Clone, Copy, Default, Debug, EnumIter
+)]
+#[repr(i32)]
+pub enum BrowseSort {
+    #[default]
+    Votes = 0,
+    GuildCount = 1,
+    Newest = 2,
+    RecentlyUpdated = 3,
+    Trending = 4,
+}
+
+#[derive(
+    Eq, TryFromPrimitive, Serialize_repr, Deserialize_repr, PartialEq, Clone, Copy, Default, Debug, EnumIter
+)]
+#[repr(i32)]
+pub enum UptimeRange {
+    #[default]
+    Day = 0,
+    Week = 1,
+    Month = 2,
+}
+
+impl UptimeRange {
+    pub fn days(self) -> i32 {
+        match self {
+            Self::Day => 1,
+            Self::Week => 7,
+            Self::Month => 30,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct UptimeQuery {
+    pub range: Option<UptimeRange>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct UptimeIncident {
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// Not set for ongoing incidents
+    pub ended_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// In seconds, up to now for ongoing incidents
+    pub duration: i64,
+}
+
+impl Default for UptimeIncident {
+    fn default() -> Self {
+        UptimeIncident {
+            started_at: chrono::Utc::now() - chrono::Duration::hours(2),
+            ended_at: Some(chrono::Utc::now() - chrono::Duration::hours(1)),
+            duration: 3600,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct BotUptime {
+    pub range: UptimeRange,
+    /// Checks during maintenance are not counted
+    pub checks: i64,
+    pub failed: i64,
+    /// ``None`` if the bot was not checked in this range
+    pub percentage: Option<f64>,
+    pub incidents: Vec<UptimeIncident>,
+}
+
+/// Query for ``GET /bots`` and ``GET /servers``. ``tags`` and ``features`` are ``|`` separated
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct BrowseQuery {
+    pub sort: Option<BrowseSort>,
+    /// Defaults to approved and certified, only approved or certified can be given
+    pub state: Option<State>,
+    pub tags: Option<String>,
+    pub tag_mode: Option<TagMode>,
+    /// Ignored for servers
+    pub features: Option<String>,
+    pub per_page: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct Browse {
+    pub results: Vec<IndexBot>,
+    /// Pass this as ``cursor`` to get the next page. None if there are no more results
+    pub next_cursor: Option<String>,
+}
+
+/// A decoded browse cursor. Like review cursors, it is bound to the sort it was created with
+pub struct BrowseCursor {
+    pub sort: BrowseSort,
+    pub key: bigdecimal::BigDecimal,
+    pub id: i64,
+    /// When the first page was fetched. Sort keys relative to the current time (``Trending``)
+    /// use this so they stay the same across pages
+    pub now: i64,
+}
+
 #[derive(Deserialize, Serialize, Clone, Default)]
 pub struct PartnerLinks {
     discord: String,
@@ -572,10 +973,161 @@ pub struct DiscordData {
     pub roles: DiscordRoles,
 }
 
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum UptimeSource {
+    /// The presence cache (baypaw)
+    Baypaw,
+    /// Fixed statuses from the config, for testing
+    Local,
+}
+
+/// Settings for the uptime checker. Loaded from uptime.json if it exists
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct UptimeConfig {
+    pub source: UptimeSource,
+    pub baypaw_url: String,
+    /// Statuses used by the local source, keyed by bot id. Bots not listed use ``local_default``
+    pub local_statuses: HashMap<String, bool>,
+    pub local_default: bool,
+    /// Owners are notified once an outage lasts this many minutes
+    pub outage_notify_minutes: i32,
+}
+
+impl Default for UptimeConfig {
+    fn default() -> Self {
+        UptimeConfig {
+            source: UptimeSource::Baypaw,
+            baypaw_url: "http://localhost:1234".to_string(),
+            local_statuses: HashMap::new(),
+            local_default: true,
+            outage_notify_minutes: 60,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum AppInfoProviderType {
+    /// japi.rest, the only provider with approximate guild counts
+    Japi,
+    /// The Discord API directly through the main bot. Gives no approximate guild counts so
+    /// stats are never checked against them
+    Discord,
+    /// No requests at all, every client id is trusted. For testing. Like ``Discord``, gives no
+    /// approximate guild counts
+    Offline,
+}
+
+/// Settings for application info lookups. Loaded from app_info.json if it exists
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct AppInfoConfig {
+    pub provider: AppInfoProviderType,
+    /// Cached application info younger than this is used without asking the provider again
+    pub fresh_minutes: u64,
+    /// Cached application info is used as a fallback for this long when the provider is down
+    pub keep_hours: u64,
+}
+
+impl Default for AppInfoConfig {
+    fn default() -> Self {
+        AppInfoConfig {
+            provider: AppInfoProviderType::Japi,
+            fresh_minutes: 60,
+            keep_hours: 24,
+        }
+    }
+}
+
+/// Application info of a bot as returned by an application info provider
+#[derive(Deserialize, Serialize, Clone, Default, Debug)]
+pub struct ApplicationInfo {
+    pub bot_id: String,
+    pub bot_public: bool,
+    /// Not every provider knows this
+    pub approximate_guild_count: Option<i64>,
+}
+
+/// A limit of ``requests`` requests every ``per_secs`` seconds
+#[derive(Deserialize, Serialize, Clone, Copy)]
+pub struct RatelimitRule {
+    pub requests: u32,
+    pub per_secs: u64,
+}
+
+/// Settings for the API rate limits. Loaded from ratelimits.json if it exists
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct RatelimitConfig {
+    pub enabled: bool,
+    /// Limit of routes without their own bucket
+    pub default: RatelimitRule,
+    /// Limits of route buckets, keyed by method and route (such as ``POST /bots/{id}/stats``)
+    pub buckets: HashMap<String, RatelimitRule>,
+    /// Take the client IP from ``Forwarded``/``X-Forwarded-For``. Only enable this when the API
+    /// runs behind a proxy that sets these headers, they can be spoofed otherwise
+    pub trust_proxy: bool,
+}
+
+impl Default for RatelimitConfig {
+    fn default() -> Self {
+        let rule = |requests, per_secs| RatelimitRule { requests, per_secs };
+
+        RatelimitConfig {
+            enabled: true,
+            default: rule(120, 60),
+            buckets: HashMap::from([
+                ("POST /bots/{id}/stats".to_string(), rule(5, 60)),
+                ("POST /bots/{id}/commands".to_string(), rule(20, 60)),
+                ("PUT /bots/{id}/commands".to_string(), rule(10, 60)),
+                ("DELETE /bots/{id}/commands".to_string(), rule(20, 60)),
+                ("POST /bots/{id}/commands/discord".to_string(), rule(5, 60)),
+            ]),
+            trust_proxy: false,
+        }
+    }
+}
+
+/// The permission level of a user as returned by baypaw (see Get User Perms)
+#[derive(Deserialize, Clone, Copy, Default)]
+pub struct StaffPerm {
+    pub perm: f32,
+}
+
+/// Settings for the review content checks. Loaded from review_filter.json if it exists
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ReviewFilterConfig {
+    pub banned_phrases: Vec<String>,
+    /// Reviews with more links than this are rejected
+    pub max_links: usize,
+    /// Accounts younger than this many days cannot review at all
+    pub min_account_age_days: i64,
+    /// Accounts younger than this many days have their reviews flagged
+    pub flag_account_age_days: i64,
+}
+
+impl Default for ReviewFilterConfig {
+    fn default() -> Self {
+        ReviewFilterConfig {
+            banned_phrases: Vec::new(),
+            max_links: 3,
+            min_account_age_days: 7,
+            flag_account_age_days: 30,
+        }
+    }
+}
+
 pub struct AppConfig {
     pub secrets: Secrets,
     pub partners: Partners,
     pub discord: DiscordData,
+    pub review_filter: ReviewFilterConfig,
+    pub uptime: UptimeConfig,
+    pub app_info: AppInfoConfig,
+    pub ratelimits: RatelimitConfig,
     pub discord_http: serenity::http::Http,
     pub discord_http_server: serenity::http::Http,
 }
@@ -618,6 +1170,46 @@ impl Default for AppConfig {
 
         let discord: DiscordData = serde_json::from_str(&discord).expect("Discord data is invalid");
 
+        // open review_filter.json, this is optional
+        let review_filter: ReviewFilterConfig = match File::open(data_dir.to_owned() + "review_filter.json") {
+            Ok(mut file) => {
+                let mut review_filter = String::new();
+                file.read_to_string(&mut review_filter).unwrap();
+                serde_json::from_str(&review_filter).expect("Review filter config is invalid")
+            },
+            Err(_) => ReviewFilterConfig::default(),
+        };
+
+        // open uptime.json, this is optional
+        let uptime: UptimeConfig = match File::open(data_dir.to_owned() + "uptime.json") {
+            Ok(mut file) => {
+                let mut uptime = String::new();
+                file.read_to_string(&mut uptime).unwrap();
+                serde_json::from_str(&uptime).expect("Uptime config is invalid")
+            },
+            Err(_) => UptimeConfig::default(),
+        };
+
+        // open app_info.json, this is optional
+        let app_info: AppInfoConfig = match File::open(data_dir.to_owned() + "app_info.json") {
+            Ok(mut file) => {
+                let mut app_info = String::new();
+                file.read_to_string(&mut app_info).unwrap();
+                serde_json::from_str(&app_info).expect("App info config is invalid")
+            },
+            Err(_) => AppInfoConfig::default(),
+        };
+
+        // open ratelimits.json, this is optional
+        let ratelimits: RatelimitConfig = match File::open(data_dir.to_owned() + "ratelimits.json") {
+            Ok(mut file) => {
+                let mut ratelimits = String::new();
+                file.read_to_string(&mut ratelimits).unwrap();
+                serde_json::from_str(&ratelimits).expect("Ratelimit config is invalid")
+            },
+            Err(_) => RatelimitConfig::default(),
+        };
+
         let token_main = secrets.token_main.clone();
         let token_squirrelflight = secrets.token_squirrelflight.clone();
 
@@ -625,6 +1217,10 @@ impl Default for AppConfig {
             secrets,
             partners,
             discord,
+            review_filter,
+            uptime,
+            app_info,
+            ratelimits,
             discord_http: serenity::http::Http::new(&token_main),
             discord_http_server: serenity::http::Http::new(&token_squirrelflight),
         }
@@ -635,6 +1231,7 @@ pub struct AppState {
     pub database: database::Database,
     pub config: AppConfig,
     pub requests: reqwest::Client,
+    pub app_info: appinfo::AppInfo,
 }
 
 #[derive(Deserialize, Serialize, Clone, Default)]
@@ -712,7 +1309,14 @@ pub struct BotEvent {
     pub event_type: BotEventType,
     pub ts: chrono::DateTime<chrono::Utc>,
     pub reason: String,
+    /// Sanitized with ammonia, use ``css_raw`` for the unsanitized version
     pub css: String,
+    pub css_raw: String,
+    pub starts_at: chrono::DateTime<chrono::Utc>,
+    /// Events without an end stay active until they are deleted
+    pub ends_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only set for maintenance events, invites are paused while the event is active
+    pub pause_invites: bool,
 }
 
 impl Default for BotEvent {
@@ -722,11 +1326,43 @@ impl Default for BotEvent {
             event_type: BotEventType::Promotion,
             ts: chrono::Utc::now(),
             reason: "Some reason here".to_string(),
-            css: "Some CSS here".to_string(),
+            css: "<style>Some CSS here</style>".to_string(),
+            css_raw: "Some CSS here".to_string(),
+            starts_at: chrono::Utc::now(),
+            ends_at: None,
+            pause_invites: false,
         }
     }
 }
 
+/// A bot event being created or edited by an owner
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct BotEventData {
+    pub event_type: BotEventType,
+    pub reason: String,
+    #[serde(default)]
+    pub css: String,
+    /// Defaults to now
+    pub starts_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub ends_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Pause invites while this event is active, ignored for anything but maintenance events
+    #[serde(default)]
+    pub pause_invites: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct GetUserBotEventPath {
+    pub user_id: i64,
+    pub bot_id: i64,
+    pub event_id: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct BotEventEndProp {
+    pub id: String,
+    pub event_type: BotEventType,
+}
+
 #[derive(Deserialize, Serialize, Clone, Default)]
 pub struct BotCommand {
     pub cmd_type: CommandType,
@@ -734,7 +1370,7 @@ pub struct BotCommand {
     pub name: String,
     pub vote_locked: bool,
     pub description: String,
-    pub args: Vec<String>,
+    pub args: Vec<CommandArg>,
     pub examples: Vec<String>,
     pub premium_only: bool,
     pub notes: Vec<String>,
@@ -743,11 +1379,120 @@ pub struct BotCommand {
     pub nsfw: bool,
 }
 
+/// An argument of a command. Older clients may still send ``<required>`` or ``[optional]`` 
+/// strings which are converted to string arguments
+#[derive(Deserialize, Serialize, Clone, Default, PartialEq)]
+#[serde(from = "CommandArgRepr")]
+pub struct CommandArg {
+    pub name: String,
+    pub arg_type: CommandArgType,
+    pub required: bool,
+    /// The only values this argument accepts, empty if anything is allowed
+    pub choices: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CommandArgRepr {
+    Text(String),
+    Full {
+        name: String,
+        #[serde(default)]
+        arg_type: CommandArgType,
+        #[serde(default)]
+        required: bool,
+        #[serde(default)]
+        choices: Vec<String>,
+    },
+}
+
+impl From<CommandArgRepr> for CommandArg {
+    fn from(repr: CommandArgRepr) -> Self {
+        match repr {
+            CommandArgRepr::Text(text) => {
+                let text = text.trim();
+                Self {
+                    name: text.trim_matches(|c| matches!(c, '<' | '>' | '[' | ']')).to_string(),
+                    required: text.starts_with('<'),
+                    ..Self::default()
+                }
+            },
+            CommandArgRepr::Full { name, arg_type, required, choices } => Self {
+                name,
+                arg_type,
+                required,
+                choices,
+            },
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, Default)]
 pub struct BotCommandVec {
     pub commands: Vec<BotCommand>,
 }
 
+#[derive(Deserialize, Serialize, Default, Clone)]
+pub struct CommandImportQuery {
+    /// Replace all slash commands instead of only adding/updating the ones given
+    pub replace: Option<bool>,
+}
+
+/// An application command in the format used by Discord. Unknown fields are ignored
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct DiscordAppCommand {
+    /// 1 is a slash command, 2 and 3 are user and message context menu commands
+    #[serde(rename = "type")]
+    pub command_type: Option<i32>,
+    pub name: String,
+    pub name_localizations: Option<IndexMap<String, String>>,
+    #[serde(default)]
+    pub description: String,
+    pub description_localizations: Option<IndexMap<String, String>>,
+    pub options: Option<Vec<DiscordAppCommandOption>>,
+    /// Set for guild commands
+    pub guild_id: Option<String>,
+    pub nsfw: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct DiscordAppCommandOption {
+    /// 1 is a subcommand, 2 is a subcommand group, anything else is an argument
+    #[serde(rename = "type")]
+    pub option_type: i32,
+    pub name: String,
+    pub name_localizations: Option<IndexMap<String, String>>,
+    #[serde(default)]
+    pub description: String,
+    pub description_localizations: Option<IndexMap<String, String>>,
+    pub required: Option<bool>,
+    pub choices: Option<Vec<DiscordAppCommandChoice>>,
+    pub options: Option<Vec<DiscordAppCommandOption>>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct DiscordAppCommandChoice {
+    pub name: String,
+    pub name_localizations: Option<IndexMap<String, String>>,
+    /// A string, integer or number
+    pub value: serde_json::Value,
+}
+
+/// A command is identified by its type and name when syncing
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct CommandSyncItem {
+    pub cmd_type: CommandType,
+    pub name: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct CommandSyncSummary {
+    pub added: Vec<CommandSyncItem>,
+    pub updated: Vec<CommandSyncItem>,
+    pub deleted: Vec<CommandSyncItem>,
+    pub unchanged: i64,
+}
+
 #[derive(Deserialize, Serialize, Default, Clone)]
 pub struct CommandDeleteQuery {
     pub nuke: Option<bool>,
@@ -906,6 +1651,11 @@ pub struct Bot {
     pub uptime_checks_failed: Option<i32>,
     pub commands: Vec<BotCommand>,
     pub events: Vec<BotEvent>,
+    /// Whether the bot has an active maintenance event
+    pub in_maintenance: bool,
+    /// The active maintenance event, if any
+    pub maintenance: Option<BotEvent>,
+    pub review_stats: ReviewStats,
     pub webhook: Option<String>,
     pub webhook_secret: Option<String>,
     pub webhook_type: Option<WebhookType>,
@@ -941,6 +1691,8 @@ impl Default for Bot {
                 chrono::Utc,
             ),
             events: vec![BotEvent::default()],
+            in_maintenance: false,
+            maintenance: None,
             long_description: "blah blah blah".to_string(),
             long_description_raw: "blah blah blah unsanitized".to_string(),
             long_description_type: LongDescriptionType::MarkdownServerSide,
@@ -971,6 +1723,7 @@ impl Default for Bot {
             uptime_checks_total: Some(30),
             uptime_checks_failed: Some(19),
             commands: vec![BotCommand::default()],
+            review_stats: ReviewStats::default(),
             webhook: Some("This will be redacted for Get Bot endpoint".to_string()),
             webhook_type: None,
             webhook_hmac_only: None,
@@ -1013,6 +1766,8 @@ pub enum EventName {
     ResourceDelete = 41,
     CommandAdd = 50,
     CommandDelete = 51,
+    BotEventStart = 60,
+    BotEventEnd = 61,
     ServerView = 70,
     ServerVote = 71,
     ServerInvite = 72,
@@ -1066,6 +1821,12 @@ pub struct BotViewProp {
     pub invite: bool,
 }
 
+#[derive(Deserialize, Serialize, Clone)]
+pub struct CommandEventProp {
+    pub cmd_type: CommandType,
+    pub name: String,
+}
+
 #[derive(Deserialize, Serialize, Clone)]
 pub struct BotVoteProp {
     pub test: bool,
@@ -1175,7 +1936,11 @@ pub struct Profile {
     pub action_logs: Vec<ActionLog>,
     pub user_experiments: Vec<UserExperiments>,
     pub flags: Vec<i32>,
-    pub extra_links: IndexMap<String, String>
+    pub extra_links: IndexMap<String, String>,
+    /// Whether NSFW bots and servers should be shown on the index by default. Left
+    /// unchanged when not given on update
+    #[serde(default)]
+    pub show_nsfw: Option<bool>,
     // TODO: Ack data
 }
 
@@ -1214,6 +1979,11 @@ pub struct RoleUpdate {
 pub struct ReviewStats {
     pub average_stars: bigdecimal::BigDecimal,
     pub total: i64,
+    /// Index ``n`` is the amount of reviews with a star rating from ``n`` to below ``n + 1``
+    pub histogram: Vec<i64>,
+    pub average_stars_30d: bigdecimal::BigDecimal,
+    pub average_stars_90d: bigdecimal::BigDecimal,
+    pub owner_responses: i64,
 }
 
 #[derive(Deserialize, Serialize, Clone, Default)]
@@ -1223,13 +1993,63 @@ pub struct ParsedReview {
     pub from: i64,
     pub stats: ReviewStats,
     pub user_review: Option<Review>,
+    pub next_cursor: Option<String>,
 }
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(
+    Eq, TryFromPrimitive, Serialize_repr, Deserialize_repr, PartialEq, Clone, Copy, Default, Debug, EnumIter
+)]
+#[repr(i32)]
+pub enum ReviewSort {
+    #[default]
+    Newest = 0,
+    Oldest = 1,
+    MostHelpful = 2,
+    HighestRated = 3,
+    LowestRated = 4,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
 pub struct ReviewQuery {
     pub target_type: TargetType,
     pub page: Option<i64>,
     pub user_id: Option<i64>,
+    pub sort: Option<ReviewSort>,
+    pub stars_from: Option<i32>,
+    pub stars_to: Option<i32>,
+    pub per_page: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+/// A prior revision of a review
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ReviewRevision {
+    pub star_rating: bigdecimal::BigDecimal,
+    pub review_text: String,
+    pub edited_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Default for ReviewRevision {
+    fn default() -> Self {
+        ReviewRevision {
+            star_rating: bigdecimal::BigDecimal::from(1),
+            review_text: "This bot is bad".to_string(),
+            edited_at: chrono::Utc::now(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct ReviewHistory {
+    pub review: Review,
+    pub revisions: Vec<ReviewRevision>,
+}
+
+/// A decoded review cursor. The cursor is bound to the sort it was created with
+pub struct ReviewCursor {
+    pub sort: ReviewSort,
+    pub key: bigdecimal::BigDecimal,
+    pub id: uuid::Uuid,
 }
 
 // Error Handling
@@ -1383,9 +2203,67 @@ pub enum BotActionMode {
     Edit,
 }
 
+#[derive(Serialize)]
+pub enum MaintenanceError {
+    InvitesPaused, // Added
+}
+
+impl APIError for MaintenanceError {
+    fn name(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    fn context(&self) -> Option<String> {
+        match self {
+            Self::InvitesPaused => Some("This bot is under maintenance and has paused invites, please try again later".to_string()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub enum BotEventError {
+    ReasonLength, // Added
+    CSSTooLong, // Added
+    EndBeforeStart, // Added
+    EndInPast, // Added
+    TooManyEvents, // Added
+    InvalidEventId, // Added
+}
+
+impl APIError for BotEventError {
+    fn name(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    fn context(&self) -> Option<String> {
+        match self {
+            Self::ReasonLength => Some("The reason of an event must be between 1 and 1000 characters".to_string()),
+            Self::CSSTooLong => Some("The CSS of an event may be at most 10000 characters".to_string()),
+            Self::EndBeforeStart => Some("An event must end after it starts".to_string()),
+            Self::EndInPast => Some("An event cannot end in the past".to_string()),
+            Self::TooManyEvents => Some("A bot may have at most 10 active or scheduled events".to_string()),
+            Self::InvalidEventId => Some("The event id must be a valid UUID".to_string()),
+        }
+    }
+}
+
 #[derive(Serialize)]
 pub enum CommandError {
-    CommandLengthError(#[serde(skip)] String),
+    NameLength(#[serde(skip)] String), // Added
+    InvalidName(#[serde(skip)] String), // Added
+    DescriptionLength(#[serde(skip)] String), // Added
+    TooManyArgs(#[serde(skip)] String), // Added
+    InvalidArg(#[serde(skip)] String, #[serde(skip)] String), // Added
+    DuplicateArg(#[serde(skip)] String, #[serde(skip)] String), // Added
+    TooManyChoices(#[serde(skip)] String, #[serde(skip)] String), // Added
+    TooManyExamples(#[serde(skip)] String), // Added
+    ExampleTooLong(#[serde(skip)] String), // Added
+    TooManyNotes(#[serde(skip)] String), // Added
+    NoteTooLong(#[serde(skip)] String), // Added
+    TooManyGroups(#[serde(skip)] String), // Added
+    InvalidDocLink(#[serde(skip)] String), // Added
+    TooManyCommands(#[serde(skip)] usize), // Added
+    DuplicateCommand(#[serde(skip)] String),
 }
 
 impl APIError for CommandError {
@@ -1396,7 +2274,21 @@ impl APIError for CommandError {
     // We have no special context really
     fn context(&self) -> Option<String> {
         match self {
-            Self::CommandLengthError(s) => Some(s.to_string()) 
+            Self::NameLength(s) => Some(format!("{:?} must be between 1 and 32 characters per word (slash commands) or 1 and 64 characters (prefix commands)", s)),
+            Self::InvalidName(s) => Some(format!("{:?} is not a valid name. Slash command names may only have up to 3 words of lowercase letters, numbers, - and _. Prefix command names may not have control characters", s)),
+            Self::DescriptionLength(s) => Some(format!("The description of {} must be between 1 and 100 characters (slash commands) or 1 and 500 characters (prefix commands)", s)),
+            Self::TooManyArgs(s) => Some(format!("{} has more than 25 arguments", s)),
+            Self::InvalidArg(s, arg) => Some(format!("Argument {:?} of {} must have a name between 1 and 32 characters without spaces", arg, s)),
+            Self::DuplicateArg(s, arg) => Some(format!("Argument {} of {} is given more than once", arg, s)),
+            Self::TooManyChoices(s, arg) => Some(format!("Argument {} of {} has more than 25 choices or an empty choice", arg, s)),
+            Self::TooManyExamples(s) => Some(format!("{} has more than 10 examples", s)),
+            Self::ExampleTooLong(s) => Some(format!("An example of {} is longer than 200 characters", s)),
+            Self::TooManyNotes(s) => Some(format!("{} has more than 10 notes", s)),
+            Self::NoteTooLong(s) => Some(format!("A note of {} is longer than 200 characters", s)),
+            Self::TooManyGroups(s) => Some(format!("{} has more than 5 groups or an empty group", s)),
+            Self::InvalidDocLink(s) => Some(format!("The doc link of {} must be a https:// link", s)),
+            Self::TooManyCommands(count) => Some(format!("A bot may have at most 250 commands, this would make it {}", count)),
+            Self::DuplicateCommand(s) => Some(format!("{} is given more than once", s)),
         }
     }
 }
@@ -1430,6 +2322,11 @@ pub enum ReviewAddError {
     ParentReviewInvalid,
     ReviewAlreadyExists,
     ReviewAlreadyVoted(#[serde(skip)] String),
+    DuplicateReview,
+    TooManyLinks(#[serde(skip)] usize),
+    ExcessiveRepetition,
+    BannedPhrase(#[serde(skip)] String),
+    AccountTooNew(#[serde(skip)] i64),
 }
 
 impl APIError for ReviewAddError {
@@ -1440,6 +2337,11 @@ impl APIError for ReviewAddError {
     fn context(&self) -> Option<String> {
         match self {
             Self::ReviewAlreadyVoted(button) => Some(format!("Click the {button} button to {button} it.", button = button)),
+            Self::DuplicateReview => Some("You have already posted this review elsewhere".to_string()),
+            Self::TooManyLinks(max) => Some(format!("Reviews may contain at most {} links", max)),
+            Self::ExcessiveRepetition => Some("Your review contains too many repeated characters".to_string()),
+            Self::BannedPhrase(phrase) => Some(format!("Your review contains a banned phrase: {}", phrase)),
+            Self::AccountTooNew(days) => Some(format!("Your account must be at least {} days old to make a review", days)),
             _ => None
         }
     }
@@ -1463,8 +2365,7 @@ pub enum CheckBotError {
     TooManyFeatures, // Added
     BannerCardError(#[serde(skip)] BannerCheckError), // Handled
     BannerPageError(#[serde(skip)] BannerCheckError), // Handled
-    JAPIError(#[serde(skip)] reqwest::Error),
-    JAPIDeserError(#[serde(skip)] reqwest::Error),
+    AppInfoUnavailable(#[serde(skip)] String), // Added
     ClientIDNeeded, // Added
     InvalidClientID, // Added
     PrivateBot, // Added
@@ -1491,8 +2392,7 @@ impl APIError for CheckBotError {
             Self::BotBannedOrDenied(s) => Some(serde_json::to_string(s).unwrap_or_default()),
             Self::BannerCardError(s) => Some(s.to_string()),
             Self::BannerPageError(s) => Some(s.to_string()),
-            Self::JAPIError(e) => Some(e.to_string()),
-            Self::JAPIDeserError(e) => Some(e.to_string()),
+            Self::AppInfoUnavailable(e) => Some(e.to_string()),
             _ => None
         }
     }
@@ -1507,6 +2407,12 @@ pub enum PackCheckError {
     InvalidBanner,
     InvalidPackId,
     DescriptionTooShort,
+    NoteTooLong,
+    TooManyEditors,
+    InvalidEditor,
+    BotNotFound(#[serde(skip)] i64), // Added
+    BotNotAllowed(#[serde(skip)] i64), // Added
+    BannerError(#[serde(skip)] BannerCheckError), // Handled
     SQLError(#[serde(skip)] sqlx::Error),
 }
 
@@ -1519,7 +2425,12 @@ impl APIError for PackCheckError {
     }
 
     fn context(&self) -> Option<String> {
-        None
+        match self {
+            Self::BotNotFound(id) => Some(format!("Bot {} does not exist", id)),
+            Self::BotNotAllowed(id) => Some(format!("Bot {} is banned, denied or NSFW and cannot be added to packs", id)),
+            Self::BannerError(e) => Some(e.to_string()),
+            _ => None,
+        }
     }
 }
 
@@ -1572,10 +2483,10 @@ impl APIError for VoteBotError {
 #[derive(Serialize, Debug)]
 pub enum StatsError {
     BadStats(#[serde(skip)] String), // TODO
-    JAPIError(#[serde(skip)] reqwest::Error),
-    JAPIDeserError(#[serde(skip)] reqwest::Error),
     SQLError(#[serde(skip)] sqlx::Error), // Added
     ClientIDNeeded,
+    ShardGuildsMismatch, // Added
+    Anomaly(#[serde(skip)] String), // Added
 }
 
 impl APIError for StatsError {
@@ -1587,8 +2498,8 @@ impl APIError for StatsError {
         match self {
             Self::SQLError(s) => Some(s.to_string()),
             Self::BadStats(s) => Some(s.to_string()),
-            Self::JAPIError(e) => Some(e.to_string()),
-            Self::JAPIDeserError(e) => Some(e.to_string()),
+            Self::ShardGuildsMismatch => Some("shard_guilds must add up to guild_count and have at most shard_count entries".to_string()),
+            Self::Anomaly(s) => Some(s.to_string()),
             _ => None,
         }
     }
@@ -1,47 +1,77 @@
 
 // Endpoints to create, delete and edit packs
 use crate::models;
+use crate::botactions;
 use actix_web::http::header::HeaderValue;
-use actix_web::{delete, patch, post, web, http, HttpRequest, HttpResponse};
+use actix_web::{delete, get, patch, post, put, web, http, HttpRequest, HttpResponse};
 use log::error;
 
 async fn pack_check(
     data: &models::AppState,
     pack: &mut models::BotPack,
 ) -> Result<(), models::PackCheckError> {
-    // Resolve the bot pack
-    if pack.resolved_bots.len() > 7 {
-        return Err(models::PackCheckError::TooManyBots);
-    }
-
     if pack.description.len() < 10 {
         return Err(models::PackCheckError::DescriptionTooShort);
     }
 
-    let mut bots = Vec::new();
+    // Deduplicate bots, keeping the first occurence (and its note)
+    let mut bots: Vec<(i64, Option<String>)> = Vec::new();
     for bot in &pack.resolved_bots {
         let parsed_id = bot.user.id.parse::<i64>();
         if parsed_id.is_err() {
             return Err(models::PackCheckError::InvalidBotId);
         }
-        bots.push(parsed_id.unwrap())
+        let parsed_id = parsed_id.unwrap();
+
+        if bots.iter().any(|(id, _)| *id == parsed_id) {
+            continue;
+        }
+
+        let note = bot.note.as_ref().map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+
+        if note.as_ref().map_or(0, |note| note.chars().count()) > 200 {
+            return Err(models::PackCheckError::NoteTooLong);
+        }
+
+        bots.push((parsed_id, note));
     }
-    // Resolve bots
-    pack.resolved_bots = data.database.resolve_pack_bots(bots).await;
 
-    if pack.resolved_bots.len() < 2 {
+    if bots.len() > 7 {
+        return Err(models::PackCheckError::TooManyBots);
+    }
+
+    if bots.len() < 2 {
         return Err(models::PackCheckError::TooFewBots);
     }
 
+    // Resolve bots, order is kept
+    pack.resolved_bots = data.database.resolve_pack_bots(bots).await?;
+
+    if pack.editors.len() > 5 {
+        return Err(models::PackCheckError::TooManyEditors);
+    }
+
+    let mut editors = Vec::new();
+    for editor in &pack.editors {
+        match editor.id.parse::<i64>() {
+            Ok(id) if editor.id != pack.owner.id && !editors.contains(&id) => editors.push(id),
+            _ => return Err(models::PackCheckError::InvalidEditor),
+        }
+    }
+
     // Possibly readd pack limits if people abuse packs?
     if !pack.icon.is_empty() && !pack.icon.starts_with("https://") {
         return Err(models::PackCheckError::InvalidIcon);
     }
 
-    if !pack.icon.is_empty() && !pack.icon.starts_with("https://") {
+    if !pack.banner.is_empty() && !pack.banner.starts_with("https://") {
         return Err(models::PackCheckError::InvalidBanner);
     }
 
+    botactions::check_banner_img(data, &pack.banner)
+        .await
+        .map_err(models::PackCheckError::BannerError)?;
+
     Ok(())
 }
 
@@ -74,13 +104,13 @@ async fn add_pack(
     let res = pack_check(data, &mut pack).await;
 
     if res.is_err() {
-        HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&res.unwrap_err()));
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&res.unwrap_err()));
     }
 
     let res = data.database.add_pack(pack).await;
 
     if res.is_err() {
-        HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&res.unwrap_err()));
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&res.unwrap_err()));
     }
 
     HttpResponse::Ok().json(models::APIResponse::ok())
@@ -90,7 +120,7 @@ async fn add_pack(
 async fn edit_pack(
     req: HttpRequest,
     info: web::Path<models::FetchBotPath>,
-    pack: web::Json<models::BotPack>,
+    mut pack: web::Json<models::BotPack>,
 ) -> HttpResponse {
     let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
     let user_id = info.id;
@@ -108,30 +138,32 @@ async fn edit_pack(
         return HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden));
     }
 
-    // Make sure we are the owner of this pack
+    // Make sure we are the owner or an editor of this pack
     let pack_owners = data.database.get_pack_owners(pack.id.clone()).await;
 
-    if let Some(owner) = pack_owners {
-        if owner != user_id {
+    let is_owner = if let Some(owner) = pack_owners {
+        if owner != user_id && !data.database.get_pack_editors(&pack.id).await.contains(&user_id) {
             return HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden));
         }
+        pack.owner.id = owner.to_string();
+        owner == user_id
     } else {
         return HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound));
-    }
+    };
 
     let mut pack = pack.into_inner();
 
     let res = pack_check(data, &mut pack).await;
 
     if res.is_err() {
-        HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&res.unwrap_err()));
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&res.unwrap_err()));
     }
 
-    let res = data.database.edit_pack(pack).await;
+    // Only the owner may change who can edit the pack
+    let res = data.database.edit_pack(pack, is_owner).await;
 
     if res.is_err() {
-        HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&res.unwrap_err()));
-
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&res.unwrap_err()));
     }
 
     HttpResponse::Ok().json(models::APIResponse::ok())
@@ -170,3 +202,63 @@ async fn delete_pack(req: HttpRequest, info: web::Path<models::GetUserPackPath>)
 
     HttpResponse::Ok().json(models::APIResponse::ok())
 }
+
+#[get("/packs/{id}")]
+async fn get_pack(req: HttpRequest, info: web::Path<models::GetPackPath>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    match data.database.get_pack(&info.id).await {
+        Some(pack) => HttpResponse::Ok().json(pack),
+        None => HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound)),
+    }
+}
+
+#[put("/users/{user_id}/packs/{pack_id}/follow")]
+async fn follow_pack(req: HttpRequest, info: web::Path<models::GetUserPackPath>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+    let user_id = info.user_id;
+
+    // Check auth
+    let auth_default = &HeaderValue::from_str("").unwrap();
+    let auth = req
+        .headers()
+        .get("Authorization")
+        .unwrap_or(auth_default)
+        .to_str()
+        .unwrap();
+    if !data.database.authorize_user(user_id, auth).await {
+        error!("Pack Follow Auth error");
+        return HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden));
+    }
+
+    match data.database.follow_pack(&info.pack_id, user_id).await {
+        Ok(true) => HttpResponse::Ok().json(models::APIResponse::ok()),
+        Ok(false) => HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound)),
+        Err(err) => HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::SQLError(err))),
+    }
+}
+
+#[delete("/users/{user_id}/packs/{pack_id}/follow")]
+async fn unfollow_pack(req: HttpRequest, info: web::Path<models::GetUserPackPath>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+    let user_id = info.user_id;
+
+    // Check auth
+    let auth_default = &HeaderValue::from_str("").unwrap();
+    let auth = req
+        .headers()
+        .get("Authorization")
+        .unwrap_or(auth_default)
+        .to_str()
+        .unwrap();
+    if !data.database.authorize_user(user_id, auth).await {
+        error!("Pack Unfollow Auth error");
+        return HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden));
+    }
+
+    match data.database.unfollow_pack(&info.pack_id, user_id).await {
+        Ok(true) => HttpResponse::Ok().json(models::APIResponse::ok()),
+        Ok(false) => HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound)),
+        Err(err) => HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::SQLError(err))),
+    }
+}
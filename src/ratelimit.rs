@@ -0,0 +1,181 @@
+/// API rate limits
+///
+/// Route buckets are the method and route of a request (``POST /bots/{id}/stats``) and their
+/// limits come from ``RatelimitConfig``. Requests with an ``Authorization`` header are counted
+/// per token and route bucket, so bots posting from the same host have their own limits. As the
+/// token is not checked here, these requests are also counted per IP and route against the
+/// default limit so made up tokens cannot get around it. Requests without a token are counted
+/// per IP and route bucket.
+///
+/// The IP is the address of the connection. ``Forwarded``/``X-Forwarded-For`` are only used
+/// when ``trust_proxy`` is set, which must only be done behind a proxy that sets them
+use crate::models;
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{http, web, Error, HttpResponse};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use std::rc::Rc;
+
+/// The state of a rate limit bucket after a request
+struct Bucket {
+    limit: u32,
+    remaining: u32,
+    /// Whether the request went over the limit
+    limited: bool,
+    /// Seconds until the bucket resets
+    reset_after: i64,
+}
+
+impl Bucket {
+    fn headers(&self) -> [(&'static str, String); 3] {
+        [
+            ("x-ratelimit-limit", self.limit.to_string()),
+            ("x-ratelimit-remaining", self.remaining.to_string()),
+            ("x-ratelimit-reset", (chrono::Utc::now().timestamp() + self.reset_after).to_string()),
+        ]
+    }
+}
+
+/// The buckets a request is counted in and their limits. Tokens are hashed so they never end
+/// up in redis
+fn client_buckets(req: &ServiceRequest, config: &models::RatelimitConfig, route: &str) -> Vec<(String, models::RatelimitRule)> {
+    let rule = *config.buckets.get(route).unwrap_or(&config.default);
+
+    let ip = if config.trust_proxy {
+        req.connection_info().realip_remote_addr().map(ToString::to_string)
+    } else {
+        req.peer_addr().map(|addr| addr.ip().to_string())
+    };
+    let ip = ip.as_deref().unwrap_or("unknown");
+
+    match req.headers().get("Authorization").and_then(|auth| auth.to_str().ok()) {
+        Some(auth) if !auth.is_empty() => {
+            let digest = ring::digest::digest(&ring::digest::SHA256, auth.as_bytes());
+            vec![
+                (format!("token:{}:{}", hex::encode(digest.as_ref()), route), rule),
+                // Kept apart from requests without a token, which have the stricter route limit
+                (format!("tokenip:{}:{}", ip, route), config.default),
+            ]
+        },
+        _ => vec![(format!("ip:{}:{}", ip, route), rule)],
+    }
+}
+
+/// Counts the request in every bucket of the client, the fullest bucket is returned
+async fn hit(data: &models::AppState, req: &ServiceRequest) -> Option<Bucket> {
+    let config = &data.config.ratelimits;
+
+    if !config.enabled {
+        return None;
+    }
+
+    // Routing has not happened yet but the pattern can already be matched
+    let route = format!(
+        "{} {}",
+        req.method(),
+        req.request().match_pattern().unwrap_or_else(|| "*".to_string())
+    );
+
+    let mut fullest: Option<Bucket> = None;
+
+    for (key, rule) in client_buckets(req, config, &route) {
+        let (count, reset_after) = data
+            .database
+            .hit_ratelimit(&key, rule.per_secs)
+            .await?;
+
+        let bucket = Bucket {
+            limit: rule.requests,
+            remaining: rule.requests.saturating_sub(count),
+            limited: count > rule.requests,
+            reset_after,
+        };
+
+        let fuller = fullest.as_ref().map_or(true, |fullest| {
+            (bucket.limited, std::cmp::Reverse(bucket.remaining), bucket.reset_after)
+                > (fullest.limited, std::cmp::Reverse(fullest.remaining), fullest.reset_after)
+        });
+
+        if fuller {
+            fullest = Some(bucket);
+        }
+    }
+
+    fullest
+}
+
+/// Middleware enforcing the rate limits, responds with a 429 once a bucket is used up
+pub struct Ratelimiter;
+
+impl<S, B> Transform<S, ServiceRequest> for Ratelimiter
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RatelimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RatelimitMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct RatelimitMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RatelimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let bucket = match req.app_data::<web::Data<models::AppState>>().cloned() {
+                Some(data) => hit(&data, &req).await,
+                None => None,
+            };
+
+            let bucket = match bucket {
+                Some(bucket) => bucket,
+                None => return Ok(service.call(req).await?.map_into_left_body()),
+            };
+
+            if bucket.limited {
+                let retry_after = bucket.reset_after.max(1);
+
+                let mut resp = HttpResponse::build(http::StatusCode::TOO_MANY_REQUESTS);
+                resp.insert_header(("Retry-After", retry_after.to_string()));
+                for header in bucket.headers() {
+                    resp.insert_header(header);
+                }
+
+                return Ok(req.into_response(resp.json(models::APIResponse::rl(retry_after))).map_into_right_body());
+            }
+
+            let mut res = service.call(req).await?;
+
+            for (name, value) in bucket.headers() {
+                if let Ok(value) = HeaderValue::from_str(&value) {
+                    res.headers_mut().insert(HeaderName::from_static(name), value);
+                }
+            }
+
+            Ok(res.map_into_left_body())
+        })
+    }
+}
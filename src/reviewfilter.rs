@@ -0,0 +1,300 @@
+/// Content checks that are run on a review before it is saved
+///
+/// To add a new check, implement ``ReviewCheck`` and add it to ``checks``
+use crate::models;
+
+/// Discord epoch in milliseconds, used to get the account age from a user id
+const DISCORD_EPOCH: i64 = 1_420_070_400_000;
+
+pub enum Verdict {
+    Pass,
+    /// The review is saved but flagged for staff to look at
+    Flag,
+    Reject(models::ReviewAddError),
+}
+
+/// Everything a check may need. Anything that needs the database is fetched beforehand
+pub struct ReviewCandidate<'a> {
+    pub user_id: i64,
+    pub text: &'a str,
+    /// Reviews by the same user with the same text on other entities
+    pub own_duplicates: i64,
+    /// Reviews by other users with the same text
+    pub other_duplicates: i64,
+    /// A reply by an owner of the reviewed entity. Owners often reuse replies across their entities
+    pub owner_reply: bool,
+}
+
+pub trait ReviewCheck {
+    fn check(&self, review: &ReviewCandidate) -> Verdict;
+}
+
+pub struct DuplicateText;
+
+impl ReviewCheck for DuplicateText {
+    fn check(&self, review: &ReviewCandidate) -> Verdict {
+        if review.owner_reply {
+            Verdict::Pass
+        } else if review.own_duplicates > 0 {
+            Verdict::Reject(models::ReviewAddError::DuplicateReview)
+        } else if review.other_duplicates > 0 {
+            Verdict::Flag
+        } else {
+            Verdict::Pass
+        }
+    }
+}
+
+pub struct LinkSpam {
+    pub max_links: usize,
+}
+
+impl ReviewCheck for LinkSpam {
+    fn check(&self, review: &ReviewCandidate) -> Verdict {
+        let text = review.text.to_lowercase();
+        let links = text.matches("http://").count() + text.matches("https://").count() + text.matches("discord.gg/").count();
+
+        if links > self.max_links {
+            Verdict::Reject(models::ReviewAddError::TooManyLinks(self.max_links))
+        } else if links > 1 {
+            Verdict::Flag
+        } else {
+            Verdict::Pass
+        }
+    }
+}
+
+pub struct Shouting;
+
+impl ReviewCheck for Shouting {
+    fn check(&self, review: &ReviewCandidate) -> Verdict {
+        let letters = review.text.chars().filter(|c| c.is_alphabetic()).count();
+        let upper = review.text.chars().filter(|c| c.is_uppercase()).count();
+
+        // Short reviews are often all caps for good reasons (acronyms etc.)
+        if letters >= 20 && upper * 10 >= letters * 8 {
+            Verdict::Flag
+        } else {
+            Verdict::Pass
+        }
+    }
+}
+
+pub struct RepeatedCharacters;
+
+impl ReviewCheck for RepeatedCharacters {
+    fn check(&self, review: &ReviewCandidate) -> Verdict {
+        let mut longest_run = 0;
+        let mut run = 0;
+        let mut last = None;
+
+        for c in review.text.chars() {
+            if Some(c) == last && !c.is_whitespace() {
+                run += 1;
+            } else {
+                run = 1;
+                last = Some(c);
+            }
+            longest_run = longest_run.max(run);
+        }
+
+        if longest_run >= 15 {
+            Verdict::Reject(models::ReviewAddError::ExcessiveRepetition)
+        } else if longest_run >= 8 {
+            Verdict::Flag
+        } else {
+            Verdict::Pass
+        }
+    }
+}
+
+pub struct BannedPhrases {
+    pub phrases: Vec<String>,
+}
+
+impl ReviewCheck for BannedPhrases {
+    fn check(&self, review: &ReviewCandidate) -> Verdict {
+        let text = review.text.to_lowercase();
+
+        for phrase in &self.phrases {
+            if !phrase.is_empty() && text.contains(&phrase.to_lowercase()) {
+                return Verdict::Reject(models::ReviewAddError::BannedPhrase(phrase.clone()));
+            }
+        }
+
+        Verdict::Pass
+    }
+}
+
+pub struct AccountAge {
+    pub min_days: i64,
+    pub flag_days: i64,
+}
+
+impl ReviewCheck for AccountAge {
+    fn check(&self, review: &ReviewCandidate) -> Verdict {
+        // Discord ids contain the time the account was created
+        let created_at = (review.user_id >> 22) + DISCORD_EPOCH;
+        let age_days = (chrono::Utc::now().timestamp_millis() - created_at) / (1000 * 60 * 60 * 24);
+
+        if age_days < self.min_days {
+            Verdict::Reject(models::ReviewAddError::AccountTooNew(self.min_days))
+        } else if age_days < self.flag_days {
+            Verdict::Flag
+        } else {
+            Verdict::Pass
+        }
+    }
+}
+
+pub fn checks(config: &models::ReviewFilterConfig) -> Vec<Box<dyn ReviewCheck>> {
+    vec![
+        Box::new(AccountAge {
+            min_days: config.min_account_age_days,
+            flag_days: config.flag_account_age_days,
+        }) as Box<dyn ReviewCheck>,
+        Box::new(BannedPhrases {
+            phrases: config.banned_phrases.clone(),
+        }),
+        Box::new(LinkSpam {
+            max_links: config.max_links,
+        }),
+        Box::new(RepeatedCharacters),
+        Box::new(Shouting),
+        Box::new(DuplicateText),
+    ]
+}
+
+/// Runs all checks. Returns whether the review should be flagged or the first rejection
+pub fn run_checks(config: &models::ReviewFilterConfig, review: &ReviewCandidate) -> Result<bool, models::ReviewAddError> {
+    let mut flagged = false;
+
+    for check in checks(config) {
+        match check.check(review) {
+            Verdict::Pass => {},
+            Verdict::Flag => flagged = true,
+            Verdict::Reject(err) => return Err(err),
+        }
+    }
+
+    Ok(flagged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(text: &str) -> ReviewCandidate {
+        ReviewCandidate {
+            // An account made in 2016
+            user_id: 185_252_419_211_018_240,
+            text,
+            own_duplicates: 0,
+            other_duplicates: 0,
+            owner_reply: false,
+        }
+    }
+
+    fn is_pass(verdict: Verdict) -> bool {
+        matches!(verdict, Verdict::Pass)
+    }
+
+    fn is_flag(verdict: Verdict) -> bool {
+        matches!(verdict, Verdict::Flag)
+    }
+
+    fn is_reject(verdict: Verdict) -> bool {
+        matches!(verdict, Verdict::Reject(_))
+    }
+
+    #[test]
+    fn duplicate_text() {
+        assert!(is_pass(DuplicateText.check(&candidate("A normal review"))));
+
+        let own = ReviewCandidate { own_duplicates: 1, ..candidate("A normal review") };
+        assert!(is_reject(DuplicateText.check(&own)));
+
+        let other = ReviewCandidate { other_duplicates: 3, ..candidate("A normal review") };
+        assert!(is_flag(DuplicateText.check(&other)));
+    }
+
+    #[test]
+    fn duplicate_owner_reply() {
+        let reply = ReviewCandidate {
+            own_duplicates: 5,
+            other_duplicates: 2,
+            owner_reply: true,
+            ..candidate("Thanks for the review!")
+        };
+        assert!(is_pass(DuplicateText.check(&reply)));
+    }
+
+    #[test]
+    fn link_spam() {
+        let check = LinkSpam { max_links: 3 };
+
+        assert!(is_pass(check.check(&candidate("Great bot, see https://example.com"))));
+        assert!(is_flag(check.check(&candidate("https://a.com and http://b.com"))));
+        assert!(is_reject(check.check(&candidate("https://a.com https://b.com discord.gg/c HTTP://D.COM"))));
+    }
+
+    #[test]
+    fn shouting() {
+        assert!(is_pass(Shouting.check(&candidate("GREAT BOT"))));
+        assert!(is_pass(Shouting.check(&candidate("This bot is really great and I like it a lot"))));
+        assert!(is_flag(Shouting.check(&candidate("THIS BOT IS REALLY GREAT AND I LIKE IT A LOT"))));
+    }
+
+    #[test]
+    fn repeated_characters() {
+        assert!(is_pass(RepeatedCharacters.check(&candidate("Sooo good"))));
+        assert!(is_flag(RepeatedCharacters.check(&candidate("Soooooooooo good"))));
+        assert!(is_reject(RepeatedCharacters.check(&candidate("Soooooooooooooooooooo good"))));
+        // Whitespace runs do not count
+        assert!(is_pass(RepeatedCharacters.check(&candidate("Good                     bot"))));
+    }
+
+    #[test]
+    fn banned_phrases() {
+        let check = BannedPhrases {
+            phrases: vec!["Free Nitro".to_string(), String::new()],
+        };
+
+        assert!(is_pass(check.check(&candidate("A normal review"))));
+        assert!(is_reject(check.check(&candidate("get free nitro here"))));
+    }
+
+    #[test]
+    fn account_age() {
+        let check = AccountAge {
+            min_days: 7,
+            flag_days: 30,
+        };
+
+        let days_ago = |days: i64| {
+            let created_at = chrono::Utc::now().timestamp_millis() - days * 24 * 60 * 60 * 1000;
+            ReviewCandidate {
+                user_id: (created_at - DISCORD_EPOCH) << 22,
+                ..candidate("A normal review")
+            }
+        };
+
+        assert!(is_reject(check.check(&days_ago(1))));
+        assert!(is_flag(check.check(&days_ago(10))));
+        assert!(is_pass(check.check(&days_ago(100))));
+    }
+
+    #[test]
+    fn run_checks_rejects_first() {
+        let config = models::ReviewFilterConfig {
+            banned_phrases: vec!["scam".to_string()],
+            max_links: 3,
+            min_account_age_days: 0,
+            flag_account_age_days: 0,
+        };
+
+        assert_eq!(run_checks(&config, &candidate("A normal review")).ok(), Some(false));
+        assert_eq!(run_checks(&config, &candidate("https://a.com and https://b.com")).ok(), Some(true));
+        assert!(run_checks(&config, &candidate("this is a scam")).is_err());
+    }
+}
@@ -2,6 +2,8 @@
 /// TODO, add websocket events *if desired*
 
 use crate::models;
+use crate::converters;
+use crate::reviewfilter;
 use actix_web::http::header::HeaderValue;
 use actix_web::{delete, get, patch, post, web, http, HttpRequest, HttpResponse};
 use bigdecimal::FromPrimitive;
@@ -26,12 +28,40 @@ async fn get_reviews(
         return HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound));
     }
 
-    let per_page = 9;
-    let offset = ((page as i64) - 1) * per_page;
+    let per_page = query.per_page.unwrap_or(9);
 
-    let reviews = data
+    if !(1..=50).contains(&per_page) {
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+    }
+
+    if let (Some(stars_from), Some(stars_to)) = (query.stars_from, query.stars_to) {
+        if stars_from > stars_to {
+            return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+        }
+    }
+
+    let mut cursor = None;
+
+    if let Some(ref raw_cursor) = query.cursor {
+        let decoded = converters::decode_review_cursor(raw_cursor);
+
+        // A cursor can only be used with the sort it was created for
+        match decoded {
+            Some(decoded) if decoded.sort == query.sort.unwrap_or_default() => cursor = Some(decoded),
+            _ => {
+                return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+            }
+        }
+    }
+
+    let offset = match (page - 1).checked_mul(per_page) {
+        Some(offset) => offset,
+        None => return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields)),
+    };
+
+    let (reviews, next_cursor) = data
         .database
-        .get_reviews(info.id, query.target_type, per_page, offset)
+        .get_reviews(info.id, &query, cursor.as_ref(), per_page, offset)
         .await;
 
     let mut parsed_review = models::ParsedReview {
@@ -43,6 +73,7 @@ async fn get_reviews(
             .get_review_stats(info.id, query.target_type)
             .await,
         user_review: None,
+        next_cursor: next_cursor.as_ref().map(converters::encode_review_cursor),
     };
 
     if let Some(user_id) = query.user_id {
@@ -58,6 +89,24 @@ async fn get_reviews(
     HttpResponse::Ok().json(parsed_review)
 }
 
+/// Whether the user owns the bot or server that was reviewed
+async fn is_target_owner(data: &models::AppState, user_id: i64, target_id: i64, target_type: models::TargetType) -> bool {
+    match target_type {
+        models::TargetType::Bot => data
+            .database
+            .get_bot_owners(target_id)
+            .await
+            .iter()
+            .any(|owner| owner.user.id == user_id.to_string()),
+        models::TargetType::Server => data
+            .database
+            .get_server(target_id)
+            .await
+            .map(|server| server.owner.id == user_id.to_string())
+            .unwrap_or(false),
+    }
+}
+
 /// Page is there are it is needed for the future
 #[post("/reviews/{id}")]
 async fn add_review(
@@ -119,6 +168,29 @@ async fn add_review(
         return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::ReviewAddError::ReviewTextError));
     }
 
+    let mut review = review.into_inner();
+
+    // Run content checks, borderline reviews are flagged instead of rejected
+    let (own_duplicates, other_duplicates) = data
+        .database
+        .get_review_duplicates(user_id, info.id, &review.review_text)
+        .await;
+
+    let owner_reply = review.parent_id.is_some() && is_target_owner(data, user_id, info.id, query.target_type).await;
+
+    let check = reviewfilter::run_checks(&data.config.review_filter, &reviewfilter::ReviewCandidate {
+        user_id,
+        text: &review.review_text,
+        own_duplicates,
+        other_duplicates,
+        owner_reply,
+    });
+
+    match check {
+        Ok(flagged) => review.flagged = flagged,
+        Err(err) => return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&err)),
+    }
+
     if query.target_type == models::TargetType::Bot {
         let bot = data.database.get_bot(info.id).await;
 
@@ -135,7 +207,7 @@ async fn add_review(
 
     let res = data
         .database
-        .add_review(review.into_inner(), user_id, info.id, query.target_type)
+        .add_review(review, user_id, info.id, query.target_type)
         .await;
 
     if res.is_err() {
@@ -208,13 +280,44 @@ async fn edit_review(
         return HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden));
     }
 
-    let res = data.database.edit_review(review.into_inner()).await;
+    let mut review = review.into_inner();
 
-    if res.is_err() {
-        return HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::GenericError::SQLError(res.unwrap_err()))); 
+    // Edits go through the same content checks as new reviews
+    let target = data.database.get_review_target(review_id).await;
+
+    let target_id = target.map_or(0, |(target_id, _)| target_id);
+
+    let (own_duplicates, other_duplicates) = data
+        .database
+        .get_review_duplicates(user_id, target_id, &review.review_text)
+        .await;
+
+    let owner_reply = match target {
+        Some((target_id, target_type)) if review_orig.parent_id.is_some() => {
+            is_target_owner(data, user_id, target_id, target_type).await
+        },
+        _ => false,
+    };
+
+    let check = reviewfilter::run_checks(&data.config.review_filter, &reviewfilter::ReviewCandidate {
+        user_id,
+        text: &review.review_text,
+        own_duplicates,
+        other_duplicates,
+        owner_reply,
+    });
+
+    match check {
+        Ok(flagged) => review.flagged = flagged,
+        Err(err) => return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&err)),
     }
 
-    HttpResponse::Ok().json(models::APIResponse::ok())
+    match data.database.edit_review(review).await {
+        Ok(true) => HttpResponse::Ok().json(models::APIResponse::ok()),
+        // Removed between the ownership check and now
+        Ok(false) => HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound)),
+        Err(err) => HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::GenericError::SQLError(err))),
+    }
 }
 
 #[delete("/reviews/{rid}")]
@@ -267,13 +370,12 @@ async fn delete_review(
         return HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden));
     }
 
-    let res = data.database.delete_review(review_id).await;
-
-    if res.is_err() {
-        return HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::GenericError::SQLError(res.unwrap_err()))); 
+    match data.database.delete_review(review_id).await {
+        Ok(true) => HttpResponse::Ok().json(models::APIResponse::ok()),
+        // Removed between the ownership check and now
+        Ok(false) => HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound)),
+        Err(err) => HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::GenericError::SQLError(err))),
     }
-
-    HttpResponse::Ok().json(models::APIResponse::ok())
 }
 
 #[patch("/reviews/{rid}/votes")]
@@ -334,3 +436,76 @@ async fn vote_review(
 
     HttpResponse::Ok().json(models::APIResponse::ok())
 }
+
+/// Only the author of the review and the owners of the reviewed entity may see its history
+#[get("/reviews/{rid}/history")]
+async fn get_review_history(
+    req: HttpRequest,
+    info: web::Path<models::ReviewDeletePath>,
+    query: web::Query<models::ReviewQuery>,
+) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    let user_id = query.user_id;
+
+    if user_id.is_none() {
+        return HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden));
+    }
+
+    let user_id = user_id.unwrap();
+
+    // Check auth
+    let auth_default = &HeaderValue::from_str("").unwrap();
+    let auth = req
+        .headers()
+        .get("Authorization")
+        .unwrap_or(auth_default)
+        .to_str()
+        .unwrap();
+    if !data.database.authorize_user(user_id, auth).await {
+        error!("Review History Auth error");
+        return HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden));
+    }
+
+    let review_id = uuid::Uuid::parse_str(&info.rid);
+    if review_id.is_err() {
+        return HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound));
+    }
+    let review_id = review_id.unwrap();
+
+    let review = data.database.get_single_review(review_id).await;
+    let target = data.database.get_review_target(review_id).await;
+
+    if review.is_none() || target.is_none() {
+        return HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound));
+    }
+
+    let review = review.unwrap();
+    let (target_id, target_type) = target.unwrap();
+
+    let mut allowed = review.user.id == user_id.to_string();
+
+    if !allowed {
+        allowed = is_target_owner(data, user_id, target_id, target_type).await;
+    }
+
+    // Bot reviewers and above
+    if !allowed {
+        allowed = data.database.is_staff(user_id, 2.0).await;
+    }
+
+    if !allowed {
+        return HttpResponse::build(http::StatusCode::FORBIDDEN).json(models::APIResponse::err_small(&models::GenericError::Forbidden));
+    }
+
+    let revisions = data.database.get_review_history(review_id).await;
+
+    if revisions.is_err() {
+        return HttpResponse::BadRequest().json(models::APIResponse::err_small(&models::GenericError::SQLError(revisions.unwrap_err())));
+    }
+
+    HttpResponse::Ok().json(models::ReviewHistory {
+        review,
+        revisions: revisions.unwrap(),
+    })
+}
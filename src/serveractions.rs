@@ -1,10 +1,11 @@
 /// Handles bot actions (view)
 
 use crate::models;
+use crate::converters;
 use std::sync::Arc;
 use uuid::Uuid;
 use actix_web::http::header::HeaderValue;
-use actix_web::{get, web, http, web::Json, HttpRequest, HttpResponse};
+use actix_web::{get, web, http, HttpRequest, HttpResponse};
 use log::{error, debug};
 
 // Server route
@@ -114,10 +115,48 @@ async fn get_server(req: HttpRequest, id: web::Path<models::FetchBotPath>) -> Ht
     }
 }
 
+// Browse Servers
+#[get("/servers")]
+async fn browse_servers(req: HttpRequest, query: web::Query<models::BrowseQuery>) -> HttpResponse {
+    let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
+
+    let per_page = query.per_page.unwrap_or(25);
+
+    if !(1..=100).contains(&per_page) {
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+    }
+
+    // Only listed servers can be browsed
+    if !matches!(query.state, None | Some(models::State::Approved | models::State::Certified)) {
+        return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+    }
+
+    let mut cursor = None;
+
+    if let Some(ref raw_cursor) = query.cursor {
+        // A cursor can only be used with the sort it was created for
+        match converters::decode_browse_cursor(raw_cursor) {
+            Some(decoded) if decoded.sort == query.sort.unwrap_or_default() => cursor = Some(decoded),
+            _ => {
+                return HttpResponse::build(http::StatusCode::BAD_REQUEST).json(models::APIResponse::err_small(&models::GenericError::InvalidFields));
+            }
+        }
+    }
+
+    let (results, next_cursor) = data.database.browse_servers(&query, cursor.as_ref(), per_page).await;
+
+    HttpResponse::Ok().json(models::Browse {
+        results,
+        next_cursor: next_cursor.map(|cursor| converters::encode_browse_cursor(&cursor)),
+    })
+}
+
 // Get Random Server
 #[get("/random-server")]
-async fn random_server(req: HttpRequest) -> Json<models::IndexBot> {
+async fn random_server(req: HttpRequest, info: web::Query<models::RandomQuery>) -> HttpResponse {
     let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
-    let server = data.database.random_server().await;
-    Json(server)
+    match data.database.random_server(info.nsfw.unwrap_or(false)).await {
+        Some(server) => HttpResponse::Ok().json(server),
+        None => HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound)),
+    }
 }
\ No newline at end of file
@@ -26,8 +26,6 @@ async fn get_botlist_stats(req: HttpRequest) -> HttpResponse {
         total_bots: data.database.get_bot_count().await,
         total_users: data.database.get_user_count().await,
         total_servers: data.database.get_server_count().await,
-        bots: data.database.get_all_bots().await,
-        servers: data.database.get_all_servers().await,
         uptime: uptime,
     })
 }
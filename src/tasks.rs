@@ -0,0 +1,63 @@
+/// Background tasks that run periodically for the lifetime of the server
+use crate::appinfo;
+use crate::models;
+use crate::uptime;
+use actix_web::web;
+use log::debug;
+use std::future::Future;
+use std::time::Duration;
+
+/// Runs ``task`` every ``every``, starting immediately
+fn every<F, Fut>(state: &web::Data<models::AppState>, every: Duration, task: F)
+where
+    F: Fn(web::Data<models::AppState>) -> Fut + 'static,
+    Fut: Future<Output = ()> + 'static,
+{
+    let state = state.clone();
+    actix_rt::spawn(async move {
+        let mut interval = actix_rt::time::interval(every);
+        loop {
+            interval.tick().await;
+            task(state.clone()).await;
+        }
+    });
+}
+
+pub fn start(state: &web::Data<models::AppState>) {
+    // Search suggestions
+    every(state, Duration::from_secs(5 * 60), |state| async move {
+        state.database.refresh_suggestions().await;
+    });
+
+    // Similar bots
+    every(state, Duration::from_secs(6 * 60 * 60), |state| async move {
+        debug!("Computing similar bots");
+        state.database.compute_similar_bots().await;
+    });
+
+    // Remove deleted, banned and denied bots from packs, unlisting packs left with less than 2 bots
+    every(state, Duration::from_secs(60 * 60), |state| async move {
+        state.database.prune_packs().await;
+    });
+
+    // Announce bot events that started or ended
+    every(state, Duration::from_secs(60), |state| async move {
+        state.database.announce_bot_events().await;
+    });
+
+    // Uptime checks
+    every(state, Duration::from_secs(5 * 60), uptime::run);
+
+    // Stats accepted while application info was unavailable
+    every(state, Duration::from_secs(15 * 60), appinfo::verify_pending_stats);
+
+    // Old uptime checks
+    every(state, Duration::from_secs(24 * 60 * 60), |state| async move {
+        state.database.prune_uptime_checks().await;
+    });
+
+    // Old stats history
+    every(state, Duration::from_secs(24 * 60 * 60), |state| async move {
+        state.database.prune_stats_history().await;
+    });
+}
@@ -0,0 +1,99 @@
+/// Uptime checks for approved bots
+///
+/// The status of a bot comes from a ``StatusSource``. To add a new source, implement
+/// ``StatusSource`` and add it to ``source``
+use crate::models;
+use actix_web::web;
+use futures::future::BoxFuture;
+use futures::stream::{self, StreamExt};
+use log::{debug, error};
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub trait StatusSource: Send + Sync {
+    /// Whether the bot is online. ``None`` means the status is not known and no check is stored
+    fn status(&self, bot_id: i64) -> BoxFuture<'_, Option<bool>>;
+}
+
+/// Asks the presence cache (baypaw) for the status of a bot
+pub struct Baypaw {
+    pub url: String,
+    pub client: reqwest::Client,
+}
+
+impl StatusSource for Baypaw {
+    fn status(&self, bot_id: i64) -> BoxFuture<'_, Option<bool>> {
+        Box::pin(async move {
+            let res = self.client
+                .get(format!("{}/getch/{}", self.url, bot_id))
+                .timeout(Duration::from_secs(10))
+                .send()
+                .await
+                .ok()?;
+
+            let user: models::User = res.json().await.ok()?;
+
+            match user.status {
+                models::Status::Unknown => None,
+                models::Status::Offline => Some(false),
+                _ => Some(true),
+            }
+        })
+    }
+}
+
+/// Stand-in source with fixed statuses, used for testing without a presence cache
+pub struct LocalStatus {
+    pub statuses: HashMap<i64, bool>,
+    pub default: bool,
+}
+
+impl StatusSource for LocalStatus {
+    fn status(&self, bot_id: i64) -> BoxFuture<'_, Option<bool>> {
+        let online = self.statuses.get(&bot_id).copied().unwrap_or(self.default);
+        Box::pin(async move { Some(online) })
+    }
+}
+
+pub fn source(config: &models::UptimeConfig) -> Box<dyn StatusSource> {
+    match config.source {
+        models::UptimeSource::Baypaw => Box::new(Baypaw {
+            url: config.baypaw_url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+        }),
+        models::UptimeSource::Local => Box::new(LocalStatus {
+            statuses: config
+                .local_statuses
+                .iter()
+                .filter_map(|(id, online)| Some((id.parse::<i64>().ok()?, *online)))
+                .collect(),
+            default: config.local_default,
+        }),
+    }
+}
+
+/// Checks every approved bot once, then notifies owners of long outages
+pub async fn run(state: web::Data<models::AppState>) {
+    let source = source(&state.config.uptime);
+
+    let bot_ids = state.database.get_uptime_bot_ids().await;
+
+    debug!("Checking uptime of {} bots", bot_ids.len());
+
+    let checks: Vec<(i64, bool)> = stream::iter(bot_ids)
+        .map(|bot_id| {
+            let source = &source;
+            async move { source.status(bot_id).await.map(|online| (bot_id, online)) }
+        })
+        .buffer_unordered(16)
+        .filter_map(|check| async move { check })
+        .collect()
+        .await;
+
+    if let Err(err) = state.database.record_uptime_checks(&checks).await {
+        error!("Error recording uptime checks: {}", err);
+        return;
+    }
+
+    state.database.notify_outages(state.config.uptime.outage_notify_minutes).await;
+}
@@ -33,7 +33,18 @@ pub async fn get_user_perms(req: HttpRequest, info: web::Path<models::FetchBotPa
 #[get("/profiles/{id}")]
 async fn get_profile(req: HttpRequest, info: web::Path<models::FetchBotPath>) -> HttpResponse {
     let data: &models::AppState = req.app_data::<web::Data<models::AppState>>().unwrap();
-    let profile = data.database.get_profile(info.id).await;
+
+    // The owner also sees their unlisted packs
+    let auth_default = &HeaderValue::from_str("").unwrap();
+    let auth = req
+        .headers()
+        .get("Authorization")
+        .unwrap_or(auth_default)
+        .to_str()
+        .unwrap_or_default();
+    let is_owner = !auth.is_empty() && data.database.authorize_user(info.id, auth).await;
+
+    let profile = data.database.get_profile(info.id, is_owner).await;
 
     if let Some(profile) = profile {
         return HttpResponse::Ok().json(profile);
@@ -61,7 +72,7 @@ async fn update_profile(
         .to_str()
         .unwrap();
     if data.database.authorize_user(info.id, auth).await {
-        let profile = data.database.get_profile(info.id).await;
+        let profile = data.database.get_profile(info.id, true).await;
         if profile.is_none() {
             return HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound));
         }
@@ -104,7 +115,7 @@ async fn receive_profile_roles(
         .to_str()
         .unwrap();
     if data.database.authorize_user(info.id, auth).await {
-        let profile = data.database.get_profile(info.id).await;
+        let profile = data.database.get_profile(info.id, true).await;
         if profile.is_none() {
             return HttpResponse::build(http::StatusCode::NOT_FOUND).json(models::APIResponse::err_small(&models::GenericError::NotFound));
         }
@@ -113,7 +124,9 @@ async fn receive_profile_roles(
         let rl = data.database.get_ratelimit(models::Ratelimit::RoleUpdate, info.id).await;
 
         if rl.is_some() && rl.unwrap() > 0 {
-            return HttpResponse::BadRequest().json(models::APIResponse::rl(rl.unwrap()));
+            return HttpResponse::build(http::StatusCode::TOO_MANY_REQUESTS)
+                .insert_header(("Retry-After", rl.unwrap().to_string()))
+                .json(models::APIResponse::rl(rl.unwrap()));
         }    
 
         if profile.state == models::UserState::ProfileEditBan {